@@ -0,0 +1,55 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use camellia::{bench, Engine};
+
+const ROWS: usize = 10_000;
+
+fn queries(c: &mut Criterion) {
+    let engine = Engine::in_memory();
+    bench::load(&engine, ROWS).unwrap();
+
+    for (name, query) in bench::QUERIES {
+        c.bench_function(name, |b| b.iter(|| engine.run_sql(query).unwrap()));
+    }
+}
+
+fn insert(c: &mut Criterion) {
+    let sql = bench::insert_sql("bench_insert", ROWS);
+    c.bench_function("insert", |b| {
+        b.iter_batched(
+            || {
+                let engine = Engine::in_memory();
+                bench::create_insert_table(&engine, "bench_insert").unwrap();
+                engine
+            },
+            |engine| engine.run_sql(&sql).unwrap(),
+            BatchSize::PerIteration,
+        )
+    });
+}
+
+// Same as insert, but on rocksdb, where rows are written by a single write batch
+fn insert_rocksdb(c: &mut Criterion) {
+    let sql = bench::insert_sql("bench_insert", ROWS);
+    let path = std::env::temp_dir().join(format!("camellia-{}-bench.db", std::process::id()));
+    c.bench_function("insert_rocksdb", |b| {
+        b.iter_batched(
+            || {
+                let _ = std::fs::remove_dir_all(&path);
+                let engine = Engine::new(&path).unwrap();
+                bench::create_insert_table(&engine, "bench_insert").unwrap();
+                engine
+            },
+            // engine is returned, so that closing database is not measured
+            |engine| {
+                engine.run_sql(&sql).unwrap();
+                engine
+            },
+            BatchSize::PerIteration,
+        )
+    });
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+criterion_group!(benches, queries, insert, insert_rocksdb);
+criterion_main!(benches);
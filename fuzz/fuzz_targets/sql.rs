@@ -0,0 +1,8 @@
+// cargo +nightly fuzz run sql -- -dict=fuzz/sql.dict
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    camellia::fuzz::run(data);
+});
@@ -0,0 +1,4 @@
+-- dialect: mysql
+CREATE TABLE t (a INT PRIMARY KEY, b TEXT);
+
+EXPLAIN SELECT 'it\'s', a FROM t WHERE b = 'back\\slash';
@@ -0,0 +1,116 @@
+// Golden plan tests: every EXPLAIN statement of plans/*.sql is run through the planner
+// and compared against plans/*.plan snapshot, other statements are executed as setup.
+//
+// To accept new plans (e.g. after optimizer change) run
+//
+//  BLESS=1 cargo test --test plans
+//
+// and review the diff of .plan files.
+//
+// Statements are parsed with generic dialect unless the file starts with e.g.
+//
+//  -- dialect: mysql
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use camellia::{Dialect, Engine, Output, ReadCsv};
+use sqllogictest::harness::{self, glob, Arguments, Failed, Trial};
+use sqlparser::ast::Statement;
+use sqlparser::parser::Parser;
+
+fn test_files() -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let paths = glob("plans/*.sql")
+        .expect("failed to find test files")
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(paths)
+}
+
+// Snapshot of all EXPLAIN statements in |sql|: each statement followed by its plan
+fn explain(sql: &str) -> Result<String, Failed> {
+    let header = sql.lines().next().unwrap_or_default();
+    let dialect = match header.strip_prefix("-- dialect:") {
+        Some(name) => name.trim().parse::<Dialect>()?,
+        None => Dialect::Generic,
+    };
+    let engine = Engine::builder()
+        .dialect(dialect)
+        .table_function("read_csv", Arc::new(ReadCsv))
+        .in_memory();
+    let program = Parser::parse_sql(dialect.parser_dialect(), sql).map_err(|e| e.to_string())?;
+
+    let mut snapshot = String::new();
+    for statement in program {
+        let is_explain = matches!(statement, Statement::Explain { .. });
+        let text = statement.to_string();
+        let output = engine
+            .run(vec![statement])
+            .map_err(|e| format!("{}: {}", text, e))?;
+
+        if !is_explain {
+            continue;
+        }
+        let rows = match output {
+            Output::Rows(rowset) => rowset.rows,
+            Output::Affected(_) => return Err(format!("{}: expected plan", text).into()),
+        };
+
+        if !snapshot.is_empty() {
+            snapshot.push('\n');
+        }
+        snapshot.push_str(&format!("-- {}\n", text));
+        for row in rows {
+            let line = row
+                .values()
+                .next()
+                .map(|v| v.to_string())
+                .unwrap_or_default();
+            snapshot.push_str(&line);
+            snapshot.push('\n');
+        }
+    }
+
+    Ok(snapshot)
+}
+
+fn test(path: &Path, bless: bool) -> Result<(), Failed> {
+    let sql = std::fs::read_to_string(path)?;
+    let actual = explain(&sql)?;
+
+    let snapshot = path.with_extension("plan");
+    if bless {
+        std::fs::write(&snapshot, actual)?;
+        return Ok(());
+    }
+
+    let expected = std::fs::read_to_string(&snapshot)
+        .map_err(|e| format!("{}: {}, run with BLESS=1", snapshot.display(), e))?;
+    if expected != actual {
+        return Err(format!(
+            "plans differ from {}, run with BLESS=1 to update\n--- expected\n{}--- actual\n{}",
+            snapshot.display(),
+            expected,
+            actual
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let bless = std::env::var_os("BLESS").is_some();
+
+    let mut tests = vec![];
+    for path in test_files()? {
+        tests.push(Trial::test(path.to_str().unwrap().to_string(), move || {
+            test(&path, bless)
+        }));
+    }
+
+    if tests.is_empty() {
+        return Err("No tests found".into());
+    }
+
+    let args = Arguments::from_args();
+    harness::run(&args, tests).exit();
+}
@@ -0,0 +1,3 @@
+EXPLAIN SELECT name FROM read_csv('plans/people.csv') WHERE age > 30 AND admin;
+
+EXPLAIN SELECT * FROM read_csv('plans/people.csv', 'n TEXT, a INT, b BOOL') AS p ORDER BY a;
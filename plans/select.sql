@@ -0,0 +1,37 @@
+CREATE TABLE t (a INT PRIMARY KEY, b INT, c TEXT);
+
+EXPLAIN SELECT 1 + 2;
+
+EXPLAIN SELECT * FROM t;
+
+EXPLAIN SELECT a, b * 2 FROM t WHERE a > 1 AND c = 'x';
+
+EXPLAIN SELECT CASE WHEN a > 1 THEN -b ELSE abs(b) END FROM t;
+
+EXPLAIN SELECT a FROM t ORDER BY b, 1;
+
+EXPLAIN VALUES (1, 'a'), (2, 'b');
+
+EXPLAIN SELECT a FROM t ORDER BY b DESC, c NULLS LAST;
+
+EXPLAIN SELECT * EXCLUDE (b) FROM t;
+
+EXPLAIN SELECT * REPLACE (a + 1 AS a) FROM t;
+
+EXPLAIN SELECT a, count(*), string_agg(c, ',' ORDER BY b DESC) FROM t GROUP BY a HAVING sum(b) > 10;
+
+EXPLAIN SELECT a FROM t ORDER BY a LIMIT 10 OFFSET 5;
+
+EXPLAIN SELECT a, b FROM t FETCH FIRST 3 ROWS ONLY;
+
+EXPLAIN SELECT a, b FROM t LIMIT 2 BY b;
+
+EXPLAIN SELECT a, b FROM t LIMIT 2 OFFSET 1 BY b;
+
+EXPLAIN SELECT 'it''s', E'tab\there';
+
+EXPLAIN SELECT value FROM generate_series(1, 10, 2) WHERE value > 3;
+
+INSERT INTO t VALUES (1, 10, 'x'), (2, 20, 'y'), (3, 30, 'z');
+
+EXPLAIN SELECT a FROM t WHERE b > 10;
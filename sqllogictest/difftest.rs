@@ -0,0 +1,51 @@
+// Random queries executed by both camellia and sqlite, see camellia::difftest
+//
+//  cargo test --features difftest --test difftest
+//
+// DIFFTEST_SEED and DIFFTEST_QUERIES override the defaults to reproduce or extend a run
+use camellia::difftest::{Differ, Generator};
+
+fn env_or(name: &str, default: u64) -> u64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[test]
+fn random_queries() {
+    let seed = env_or("DIFFTEST_SEED", 42);
+    let queries = env_or("DIFFTEST_QUERIES", 1000);
+
+    let differ = Differ::new().unwrap();
+    let generator = Generator::new(seed, &["a", "b", "c"]);
+    differ
+        .execute("create table t(a int, b int, c int)")
+        .unwrap();
+    for _ in 0..100 {
+        let sql = format!("insert into t values {}", generator.row());
+        differ.execute(&sql).unwrap();
+    }
+
+    for _ in 0..queries {
+        let (sql, params) = generator.query("t");
+        if let Err(e) = differ.compare(&sql, &params) {
+            panic!("seed {}: {}", seed, e);
+        }
+    }
+}
+
+#[test]
+fn literals() {
+    let differ = Differ::new().unwrap();
+    for sql in [
+        "select 1",
+        "select -7 % 3",
+        "select abs(-2), 2 + 2 * 2",
+        "select case when 1 > 2 then 1 else 2 end",
+        "select 'text'",
+        "select true, false",
+    ] {
+        differ.compare(sql, &[]).unwrap();
+    }
+}
@@ -4,10 +4,11 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use camellia::{Column, Engine, Output, RowSet, Type};
+use camellia::difftest::sqlite_value;
+use camellia::{Column, Engine, Output, RowSet, Type, Value};
 use sqllogictest::{
     harness::{self, glob, Arguments, Failed, Trial},
-    DBOutput, DefaultColumnType, MakeConnection, Runner,
+    DBOutput, DefaultColumnType, MakeConnection, Record, Runner,
 };
 struct BoxError(Box<dyn Error + Send + Sync + 'static>);
 
@@ -26,21 +27,38 @@ impl fmt::Debug for BoxError {
 impl Error for BoxError {}
 
 struct Database {
-    engine: Engine,
+    engine: Option<Engine>,
+    path: PathBuf,
 }
 
 impl Database {
-    fn new() -> Self {
-        const PATH: &str = "camellia.test.db";
-
-        if std::path::Path::new(PATH).exists() {
-            std::fs::remove_dir_all(PATH).unwrap();
+    // Each test file gets its own directory, so that tests can run in parallel
+    fn new(test: &Path) -> Self {
+        let name = test.file_stem().unwrap().to_string_lossy();
+        let path =
+            std::env::temp_dir().join(format!("camellia-{}-{}.test.db", std::process::id(), name));
+
+        if path.exists() {
+            std::fs::remove_dir_all(&path).unwrap();
         }
 
         Database {
-            engine: Engine::new(PATH).unwrap(),
+            engine: Some(Engine::new(&path).unwrap()),
+            path,
         }
     }
+
+    fn engine(&self) -> &Engine {
+        self.engine.as_ref().unwrap()
+    }
+}
+
+impl Drop for Database {
+    fn drop(&mut self) {
+        // close database before removing its files
+        drop(self.engine.take());
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
 }
 
 impl sqllogictest::DB for Database {
@@ -52,7 +70,7 @@ impl sqllogictest::DB for Database {
     }
 
     fn run(&mut self, sql: &str) -> Result<DBOutput<Self::ColumnType>, Self::Error> {
-        match self.engine.run_sql(sql) {
+        match self.engine().run_sql(sql) {
             Ok(Output::Rows(rows)) => Ok(convert(rows)),
             Ok(Output::Affected(n)) => Ok(DBOutput::StatementComplete(n as u64)),
             Err(e) => Err(BoxError(e)),
@@ -74,32 +92,31 @@ fn convert(rowset: RowSet) -> DBOutput<DefaultColumnType> {
     let rows = rowset
         .rows
         .iter()
-        .map(|row| row.values().map(|val| val.to_string()).collect())
+        .map(|row| row.values().map(value_to_text).collect())
         .collect();
 
     DBOutput::Rows { types, rows }
 }
 
+// Same as sqlite output, see camellia::difftest::sqlite_value
+fn value_to_text(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        value => value.to_string(),
+    }
+}
+
 struct Sqlite(rusqlite::Connection);
 
 impl Sqlite {
     fn new() -> Self {
         let c = rusqlite::Connection::open_in_memory().unwrap();
+        // generate_series
+        rusqlite::vtab::series::load_module(&c).unwrap();
         Sqlite(c)
     }
 }
 
-fn value_to_string(v: rusqlite::types::ValueRef) -> String {
-    use rusqlite::types::ValueRef;
-    match v {
-        ValueRef::Null => "NULL".to_string(),
-        ValueRef::Integer(i) => i.to_string(),
-        ValueRef::Real(r) => r.to_string(),
-        ValueRef::Text(s) => std::str::from_utf8(s).unwrap().to_string(),
-        ValueRef::Blob(_) => todo!(),
-    }
-}
-
 impl sqllogictest::DB for Sqlite {
     type Error = rusqlite::Error;
     type ColumnType = DefaultColumnType;
@@ -124,7 +141,7 @@ impl sqllogictest::DB for Sqlite {
                 let mut row_output = vec![];
                 for i in 0..column_count {
                     let row = row.get_ref(i)?;
-                    row_output.push(value_to_string(row));
+                    row_output.push(sqlite_value(row));
                 }
                 output.push(row_output);
             }
@@ -150,33 +167,51 @@ fn test_files() -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
     // - https://github.com/cockroachdb/cockroach/tree/master/pkg/sql/logictest/testdata/logic_test
     // - https://github.com/duckdb/duckdb/tree/main/test/sql
     let pattern = "sqllogictest/*.test";
-    let paths = glob(pattern)
+    let mut paths = glob(pattern)
         .expect("failed to find test files")
         .collect::<Result<Vec<_>, _>>()?;
-    // TODO: make it pass
-    // paths.push(PathBuf::from("sqllogictest/sqlite-tests/test/select1.test"));
+    paths.push(PathBuf::from(SQLITE_TESTS).join("test/select1.test"));
     Ok(paths)
 }
 
+// sqlite corpus, expected results there are recorded by sqlite itself
+const SQLITE_TESTS: &str = "sqllogictest/sqlite-tests";
+
+// Queries of sqlite corpus using features which are not supported yet: scalar and
+// correlated subqueries, EXISTS and avg
+const UNSUPPORTED: &[&str] = &["(select", "exists", "avg("];
+
+fn is_supported(record: &Record<DefaultColumnType>) -> bool {
+    let sql = match record {
+        Record::Statement { sql, .. } | Record::Query { sql, .. } => sql.to_ascii_lowercase(),
+        _ => return true,
+    };
+    !UNSUPPORTED.iter().any(|feature| sql.contains(feature))
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut tests = vec![];
     for path in test_files()? {
-        tests.push(Trial::test(path.to_str().unwrap().to_string(), move || {
+        // reported as ignored until submodule is checked out: git submodule update --init
+        let missing = !path.exists();
+        let trial = Trial::test(path.to_str().unwrap().to_string(), move || {
             test(
                 &path,
-                || async { Ok(Database::new()) },
+                || {
+                    let path = path.clone();
+                    async move { Ok(Database::new(&path)) }
+                },
                 || async { Ok(Sqlite::new()) },
             )
-        }));
+        });
+        tests.push(trial.with_ignored_flag(missing));
     }
 
     if tests.is_empty() {
         return Err("No tests found".into());
     }
 
-    let mut args = Arguments::from_args();
-    // TODO: support multithreaded testing
-    args.test_threads = Some(1);
+    let args = Arguments::from_args();
     harness::run(&args, tests).exit();
 }
 
@@ -186,6 +221,13 @@ fn test(
     make_sqlite_conn: impl MakeConnection,
 ) -> Result<(), Failed> {
     let filename = filename.as_ref();
+    let mut tester = Runner::new(make_conn);
+    if filename.starts_with(SQLITE_TESTS) {
+        let records = sqllogictest::parse_file(filename)?;
+        tester.run_multi(records.into_iter().filter(is_supported))?;
+        return Ok(());
+    }
+
     let mut sqlite_tester = Runner::new(make_sqlite_conn);
     futures::executor::block_on(sqlite_tester.update_test_file(
         filename,
@@ -194,7 +236,6 @@ fn test(
         sqllogictest::default_column_validator,
     ))?;
 
-    let mut tester = Runner::new(make_conn);
     tester.run_file(filename)?;
     Ok(())
 }
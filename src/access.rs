@@ -0,0 +1,39 @@
+// Per-table access control of embedders, consulted while planning statements.
+// E.g. multi-tenant filtering without changing application SQL:
+//
+//  struct Tenant(i64);
+//
+//  impl AccessHook for Tenant {
+//      fn predicate(&self, table: &str, _access: Access) -> Option<String> {
+//          (table == "orders").then(|| format!("tenant_id = {}", self.0))
+//      }
+//  }
+//
+//  let engine = Engine::builder().access_hook(Arc::new(Tenant(42))).in_memory();
+//
+// NOTE: predicates are parsed with the generic dialect, whatever the dialect of sessions is.
+// Tables which hooks hide, fully or partially, are not reported by SHOW STATUS
+use crate::types::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    // SELECT from the table
+    Read,
+    Insert,
+    Create,
+    Drop,
+}
+
+pub trait AccessHook: Send + Sync {
+    // Err rejects the whole statement
+    fn check(&self, _table: &str, _access: Access) -> Result<()> {
+        Ok(())
+    }
+
+    // SQL predicate over columns of |table|: for Access::Read only matching rows are visible,
+    // for Access::Insert the statement fails unless all inserted rows match.
+    // None => no restriction
+    fn predicate(&self, _table: &str, _access: Access) -> Option<String> {
+        None
+    }
+}
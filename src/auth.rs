@@ -0,0 +1,144 @@
+// Authentication of network clients and per-user permissions, enforced by Session:
+//
+//  let users = StaticUsers::new()
+//      .user("admin", "secret", Permissions::ALL)
+//      .user("reader", "secret", Permissions::READ_ONLY);
+//  let mut session = Session::authenticate(engine, &users, "reader", "secret")?;
+//  session.execute("DROP TABLE t", &[]);      // Err, DDL is not allowed
+use std::collections::HashMap;
+use std::fmt;
+
+use sqlparser::ast;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementClass {
+    // queries, EXPLAIN and SHOW
+    Read,
+    // statements which modify rows, e.g. INSERT
+    Write,
+    // everything else, e.g. CREATE TABLE
+    Ddl,
+    // SET, changes only the state of the session and is allowed for every user
+    Session,
+}
+
+impl StatementClass {
+    pub fn of(statement: &ast::Statement) -> Self {
+        match statement {
+            ast::Statement::Query(_)
+            | ast::Statement::Explain { .. }
+            | ast::Statement::ShowVariable { .. } => StatementClass::Read,
+            ast::Statement::Insert { .. }
+            | ast::Statement::Update { .. }
+            | ast::Statement::Delete { .. } => StatementClass::Write,
+            ast::Statement::SetVariable { .. } => StatementClass::Session,
+            _ => StatementClass::Ddl,
+        }
+    }
+}
+
+impl fmt::Display for StatementClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatementClass::Read => write!(f, "read"),
+            StatementClass::Write => write!(f, "write"),
+            StatementClass::Ddl => write!(f, "DDL"),
+            StatementClass::Session => write!(f, "session"),
+        }
+    }
+}
+
+// Statement classes allowed for a user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Permissions {
+    pub read: bool,
+    pub write: bool,
+    pub ddl: bool,
+}
+
+impl Permissions {
+    pub const ALL: Permissions = Permissions {
+        read: true,
+        write: true,
+        ddl: true,
+    };
+    pub const READ_WRITE: Permissions = Permissions {
+        read: true,
+        write: true,
+        ddl: false,
+    };
+    pub const READ_ONLY: Permissions = Permissions {
+        read: true,
+        write: false,
+        ddl: false,
+    };
+
+    pub fn allows(&self, class: StatementClass) -> bool {
+        match class {
+            StatementClass::Read => self.read,
+            StatementClass::Write => self.write,
+            StatementClass::Ddl => self.ddl,
+            StatementClass::Session => true,
+        }
+    }
+}
+
+pub trait Authenticator: Send + Sync {
+    // None => credentials are not valid
+    fn authenticate(&self, user: &str, password: &str) -> Option<Permissions>;
+}
+
+// Check credentials with callback, e.g. against external user database
+impl<F> Authenticator for F
+where
+    F: Fn(&str, &str) -> Option<Permissions> + Send + Sync,
+{
+    fn authenticate(&self, user: &str, password: &str) -> Option<Permissions> {
+        self(user, password)
+    }
+}
+
+// Fixed set of users, e.g. from the config file of the server
+#[derive(Default)]
+pub struct StaticUsers {
+    // name => (password, permissions)
+    users: HashMap<String, (String, Permissions)>,
+}
+
+impl StaticUsers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn user(mut self, name: &str, password: &str, permissions: Permissions) -> Self {
+        self.users
+            .insert(name.to_owned(), (password.to_owned(), permissions));
+        self
+    }
+}
+
+impl Authenticator for StaticUsers {
+    fn authenticate(&self, user: &str, password: &str) -> Option<Permissions> {
+        let (expected, permissions) = self.users.get(user)?;
+        constant_time_eq(expected.as_bytes(), password.as_bytes()).then_some(*permissions)
+    }
+}
+
+// Doesn't leak the length of matching prefix through timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// (user, password) of "Basic <base64 of user:password>" authorization header
+#[cfg(any(feature = "http", feature = "grpc"))]
+pub(crate) fn parse_basic(header: &str) -> Option<(String, String)> {
+    use base64::Engine as _;
+
+    let encoded = header.strip_prefix("Basic ")?;
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (user, password) = decoded.split_once(':')?;
+    Some((user.to_owned(), password.to_owned()))
+}
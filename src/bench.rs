@@ -0,0 +1,117 @@
+// Synthetic dataset and workloads measured by criterion benches (benches/operators.rs)
+// and `camellia bench`, so that both report comparable numbers
+//
+// NOTE: uses Instant, which is not available on wasm32-unknown-unknown
+use std::time::{Duration, Instant};
+
+use crate::engine::Engine;
+use crate::types::Result;
+
+// Table with the dataset, see load
+pub const TABLE: &str = "bench";
+
+// (name, query over TABLE), every query reads all rows of the table
+pub const QUERIES: &[(&str, &str)] = &[
+    ("scan", "SELECT count(*) FROM bench"),
+    ("filter", "SELECT count(*) FROM bench WHERE a % 10 = 3"),
+    (
+        "sort",
+        "SELECT id, a FROM bench ORDER BY a DESC, id LIMIT 10",
+    ),
+    // full scan until there are indexes
+    ("point_lookup", "SELECT * FROM bench WHERE id = 4242"),
+];
+
+const COLUMNS: &str = "(id INT PRIMARY KEY, a INT, b TEXT)";
+
+// Create TABLE with |rows| rows: sequential id, pseudo-random a and short text b
+pub fn load(engine: &Engine, rows: usize) -> Result<()> {
+    engine.run_sql(&format!("CREATE TABLE {} {}", TABLE, COLUMNS))?;
+    engine.run_sql(&format!(
+        "INSERT INTO {} SELECT value, value * 7919 % 10007, \
+         CASE WHEN value % 2 = 0 THEN 'even' ELSE 'odd' END \
+         FROM generate_series(1, {})",
+        TABLE, rows
+    ))?;
+    Ok(())
+}
+
+// Create empty |table| with the same columns as TABLE
+pub fn create_insert_table(engine: &Engine, table: &str) -> Result<()> {
+    engine.run_sql(&format!("CREATE TABLE {} {}", table, COLUMNS))?;
+    Ok(())
+}
+
+// Multi-row INSERT of |rows| literal rows into table created by create_insert_table
+pub fn insert_sql(table: &str, rows: usize) -> String {
+    let values: Vec<_> = (1..=rows)
+        .map(|id| format!("({}, {}, 'row {}')", id, id * 7919 % 10007, id))
+        .collect();
+    format!("INSERT INTO {} VALUES {}", table, values.join(", "))
+}
+
+pub struct Measurement {
+    pub name: &'static str,
+    pub iterations: u32,
+    // sum over all iterations
+    pub total: Duration,
+    // rows read or written by single iteration
+    pub rows: usize,
+}
+
+impl Measurement {
+    pub fn mean(&self) -> Duration {
+        self.total / self.iterations.max(1)
+    }
+
+    pub fn rows_per_sec(&self) -> f64 {
+        let rows = self.rows as f64 * self.iterations as f64;
+        rows / self.total.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+// Load dataset of |rows| rows into |engine| and run each workload |iterations| times
+pub fn run(engine: &Engine, rows: usize, iterations: u32) -> Result<Vec<Measurement>> {
+    let start = Instant::now();
+    load(engine, rows)?;
+    let mut measurements = vec![Measurement {
+        name: "load",
+        iterations: 1,
+        total: start.elapsed(),
+        rows,
+    }];
+
+    for &(name, query) in QUERIES {
+        let mut total = Duration::ZERO;
+        for _ in 0..iterations {
+            let start = Instant::now();
+            engine.run_sql(query)?;
+            total += start.elapsed();
+        }
+        measurements.push(Measurement {
+            name,
+            iterations,
+            total,
+            rows,
+        });
+    }
+
+    // table is recreated outside of measured time, so that keys don't collide
+    let sql = insert_sql("bench_insert", rows);
+    let mut total = Duration::ZERO;
+    for _ in 0..iterations {
+        create_insert_table(engine, "bench_insert")?;
+        let start = Instant::now();
+        engine.run_sql(&sql)?;
+        total += start.elapsed();
+        engine.run_sql("DROP TABLE bench_insert")?;
+    }
+    measurements.push(Measurement {
+        name: "insert",
+        iterations,
+        total,
+        rows,
+    });
+
+    Ok(measurements)
+}
@@ -0,0 +1,250 @@
+// Connection/Statement/Rows API shaped after rusqlite, to ease moving small apps from SQLite
+//
+//  let conn = Connection::open_in_memory()?;
+//  conn.execute("create table t(id int primary key, name text)", params![])?;
+//  conn.execute("insert into t values(?, ?)", params![1, "foo"])?;
+//
+//  let mut stmt = conn.prepare("select id, name from t where id = ?")?;
+//  let names = stmt.query_map(params![1], |row| row.get::<_, String>("name"))?;
+//  for name in names {
+//      println!("{}", name?);
+//  }
+#[cfg(feature = "rocksdb")]
+use std::path::Path;
+
+use sqlparser::ast;
+
+use crate::engine::{self, Engine, Output};
+use crate::schema::Schema;
+use crate::types::{self, Result, Value};
+
+// Builds &[Value] out of anything convertable to Value
+#[macro_export]
+macro_rules! params {
+    () => {
+        &[] as &[$crate::Value]
+    };
+    ($($param:expr),+ $(,)?) => {
+        &[$($crate::Value::from($param)),+] as &[$crate::Value]
+    };
+}
+
+pub struct Connection {
+    engine: Engine,
+}
+
+impl Connection {
+    #[cfg(feature = "rocksdb")]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let engine = Engine::new(path)?;
+        Ok(Connection { engine })
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Ok(Connection {
+            engine: Engine::in_memory(),
+        })
+    }
+
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    // Run single statement, returns number of affected rows
+    pub fn execute(&self, sql: &str, params: &[Value]) -> Result<usize> {
+        self.prepare(sql)?.execute(params)
+    }
+
+    // Run all statements from |sql| one by one, parameters are not supported
+    pub fn execute_batch(&self, sql: &str) -> Result<()> {
+        for statement in self.engine.parse(sql)? {
+            self.engine.run(vec![statement])?;
+        }
+
+        Ok(())
+    }
+
+    pub fn prepare(&self, sql: &str) -> Result<Statement<'_>> {
+        let program = self.engine.parse(sql)?;
+        if program.len() != 1 {
+            return Err("Cannot prepare more than one statement at time".into());
+        }
+
+        Ok(Statement {
+            conn: self,
+            program,
+        })
+    }
+
+    // Run query and map first row of result with |f|, fails if the result is empty
+    pub fn query_row<T, F>(&self, sql: &str, params: &[Value], f: F) -> Result<T>
+    where
+        F: FnOnce(&Row<'_>) -> Result<T>,
+    {
+        let mut statement = self.prepare(sql)?;
+        let mut rows = statement.query(params)?;
+        match rows.next() {
+            Some(row) => f(&row),
+            None => Err("Query returned no rows".into()),
+        }
+    }
+}
+
+pub struct Statement<'conn> {
+    conn: &'conn Connection,
+    program: Vec<ast::Statement>,
+}
+
+impl<'conn> Statement<'conn> {
+    pub fn execute(&mut self, params: &[Value]) -> Result<usize> {
+        match self.run(params)? {
+            Output::Affected(n) => Ok(n),
+            Output::Rows(_) => Err("Execute returned results, use query instead".into()),
+        }
+    }
+
+    pub fn query(&mut self, params: &[Value]) -> Result<Rows> {
+        match self.run(params)? {
+            Output::Rows(rowset) => Ok(Rows {
+                schema: rowset.schema,
+                rows: rowset.rows.into_iter(),
+            }),
+            Output::Affected(_) => Err("Statement is not a query".into()),
+        }
+    }
+
+    pub fn query_map<T, F>(&mut self, params: &[Value], f: F) -> Result<MappedRows<F>>
+    where
+        F: FnMut(&Row<'_>) -> Result<T>,
+    {
+        let rows = self.query(params)?;
+        Ok(MappedRows { rows, f })
+    }
+
+    fn run(&self, params: &[Value]) -> Result<Output> {
+        let mut program = self.program.clone();
+        engine::bind(&mut program, params)?;
+        self.conn.engine.run(program)
+    }
+}
+
+pub struct Rows {
+    schema: Schema,
+    rows: std::vec::IntoIter<types::Row>,
+}
+
+impl Rows {
+    pub fn column_names(&self) -> Vec<&str> {
+        self.schema
+            .columns()
+            .map(|column| column.name.as_str())
+            .collect()
+    }
+
+    // NOTE: intentionally not an Iterator, rows borrow column info from |self|
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Row<'_>> {
+        let values = self.rows.next()?;
+        Some(Row {
+            schema: &self.schema,
+            values,
+        })
+    }
+}
+
+pub struct MappedRows<F> {
+    rows: Rows,
+    f: F,
+}
+
+impl<T, F> Iterator for MappedRows<F>
+where
+    F: FnMut(&Row<'_>) -> Result<T>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let row = self.rows.next()?;
+        Some((self.f)(&row))
+    }
+}
+
+pub struct Row<'stmt> {
+    schema: &'stmt Schema,
+    values: types::Row,
+}
+
+impl<'stmt> Row<'stmt> {
+    // Get value of column by its index or name
+    pub fn get<I: RowIndex, T: FromValue>(&self, index: I) -> Result<T> {
+        let index = index.index(self.schema)?;
+        T::from_value(self.values.get(index))
+    }
+}
+
+pub trait RowIndex {
+    fn index(&self, schema: &Schema) -> Result<usize>;
+}
+
+impl RowIndex for usize {
+    fn index(&self, schema: &Schema) -> Result<usize> {
+        if *self >= schema.columns.len() {
+            return Err(format!("Invalid column index: {}", self).into());
+        }
+
+        Ok(*self)
+    }
+}
+
+impl RowIndex for &str {
+    fn index(&self, schema: &Schema) -> Result<usize> {
+        schema
+            .columns()
+            .position(|column| column.name == *self)
+            .ok_or_else(|| format!("Invalid column name: {}", self).into())
+    }
+}
+
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self>;
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Result<Self> {
+        Ok(value.clone())
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self> {
+        value
+            .to_bool()
+            .ok_or_else(|| format!("Cannot convert {} to bool", value.type_()).into())
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: &Value) -> Result<Self> {
+        value
+            .to_int()
+            .ok_or_else(|| format!("Cannot convert {} to int", value.type_()).into())
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::String(val) => Ok(val.clone()),
+            _ => Err(format!("Cannot convert {} to text", value.type_()).into()),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::Null => Ok(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
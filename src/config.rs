@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use crate::output::{Mode, Redirect};
+
+// REPL state changed by dot-commands, initialized from the config file
+pub struct Settings {
+    // print time spent executing statements
+    pub timing: bool,
+    // show at most this many rows of result
+    pub maxrows: Option<usize>,
+    // pipe results which don't fit into terminal through $PAGER
+    pub pager: bool,
+    // format of printed results
+    pub mode: Mode,
+    // text printed in place of NULL
+    pub nullvalue: String,
+    // results are written here instead of terminal, set by .output/.once
+    pub output: Option<Redirect>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            timing: false,
+            maxrows: None,
+            pager: true,
+            mode: Mode::Table,
+            nullvalue: "null".to_owned(),
+            output: None,
+        }
+    }
+}
+
+// $XDG_CONFIG_HOME/camellia/config on linux
+pub fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("camellia").join("config"))
+}
+
+// $XDG_DATA_HOME/camellia/history on linux
+pub fn history_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("camellia").join("history"))
+}
@@ -0,0 +1,62 @@
+// SQL dialect used to parse statements, e.g. to run scripts exported from other databases:
+//
+//  let engine = Engine::builder().dialect(Dialect::MySql).in_memory();
+//  let mut session = Session::new(Arc::new(engine));
+//  session.execute("SET dialect = 'postgres'", &[])?;
+//
+// Engine::run_sql always uses the dialect of the builder, SET dialect changes only the session
+//
+// NOTE: only the syntax is affected (e.g. backslash escapes in MySQL strings),
+// statements are executed the same way regardless of dialect
+use std::fmt;
+use std::str::FromStr;
+
+use sqlparser::dialect::{GenericDialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    #[default]
+    Generic,
+    Sqlite,
+    Postgres,
+    MySql,
+}
+
+impl Dialect {
+    pub fn parser_dialect(&self) -> &'static dyn sqlparser::dialect::Dialect {
+        match self {
+            Dialect::Generic => &GenericDialect {},
+            Dialect::Sqlite => &SQLiteDialect {},
+            Dialect::Postgres => &PostgreSqlDialect {},
+            Dialect::MySql => &MySqlDialect {},
+        }
+    }
+}
+
+impl FromStr for Dialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "generic" => Ok(Dialect::Generic),
+            "sqlite" => Ok(Dialect::Sqlite),
+            "postgres" | "postgresql" => Ok(Dialect::Postgres),
+            "mysql" => Ok(Dialect::MySql),
+            _ => Err(format!(
+                "Unknown dialect: {}, expected generic, sqlite, postgres or mysql",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dialect::Generic => write!(f, "generic"),
+            Dialect::Sqlite => write!(f, "sqlite"),
+            Dialect::Postgres => write!(f, "postgres"),
+            Dialect::MySql => write!(f, "mysql"),
+        }
+    }
+}
@@ -0,0 +1,257 @@
+// Differential testing against SQLite: statements are executed by both engines and
+// query results are compared after normalization, see sqllogictest/difftest.rs
+//
+//  let differ = Differ::new()?;
+//  differ.execute("create table t(a int, b int)")?;
+//  differ.compare("select a + ? from t", &[Value::Int(1)])?;
+//
+// NOTE: NULLs, division and overflow are not generated, camellia intentionally differs
+// from sqlite there (see sqllogictest/expr.test)
+use rusqlite::types::ValueRef;
+
+use crate::engine::{Engine, Output};
+use crate::env::Env;
+use crate::types::{Result, Value};
+
+// Result rows rendered the same way by both engines, in unspecified order
+pub type Rows = Vec<Vec<String>>;
+
+pub struct Differ {
+    engine: Engine,
+    sqlite: rusqlite::Connection,
+}
+
+impl Differ {
+    pub fn new() -> Result<Self> {
+        Ok(Differ {
+            engine: Engine::in_memory(),
+            sqlite: rusqlite::Connection::open_in_memory()?,
+        })
+    }
+
+    // Run statement which doesn't return rows, e.g. CREATE TABLE or INSERT, on both engines.
+    // Fails unless both engines succeed or both fail
+    pub fn execute(&self, sql: &str) -> Result<()> {
+        let ours = self.engine.run_sql(sql).map(|_| ());
+        let theirs = self.sqlite.execute(sql, []).map(|_| ());
+        match (ours, theirs) {
+            (Ok(()), Err(e)) => Err(format!("{}: only sqlite failed: {}", sql, e).into()),
+            (Err(e), Ok(())) => Err(format!("{}: only camellia failed: {}", sql, e).into()),
+            _ => Ok(()),
+        }
+    }
+
+    // Run query with |params| bound to placeholders on both engines and compare results
+    pub fn compare(&self, sql: &str, params: &[Value]) -> Result<()> {
+        let ours = self.query_camellia(sql, params);
+        let theirs = self.query_sqlite(sql, params);
+        match (ours, theirs) {
+            (Ok(ours), Ok(theirs)) if ours == theirs => Ok(()),
+            (Ok(ours), Ok(theirs)) => Err(format!(
+                "{} {:?}: results differ\ncamellia: {:?}\nsqlite:   {:?}",
+                sql, params, ours, theirs
+            )
+            .into()),
+            (Ok(_), Err(e)) => {
+                Err(format!("{} {:?}: only sqlite failed: {}", sql, params, e).into())
+            }
+            (Err(e), Ok(_)) => {
+                Err(format!("{} {:?}: only camellia failed: {}", sql, params, e).into())
+            }
+            (Err(_), Err(_)) => Ok(()),
+        }
+    }
+
+    fn query_camellia(&self, sql: &str, params: &[Value]) -> Result<Rows> {
+        let rowset = match self.engine.run_sql_with_params(sql, params)? {
+            Output::Rows(rowset) => rowset,
+            Output::Affected(_) => return Err("Expected rows".into()),
+        };
+
+        let rows = rowset
+            .rows
+            .iter()
+            .map(|row| row.values().map(normalize).collect())
+            .collect();
+        Ok(sorted(rows))
+    }
+
+    fn query_sqlite(&self, sql: &str, params: &[Value]) -> Result<Rows> {
+        let params = params.iter().map(|value| match value {
+            Value::Null => rusqlite::types::Value::Null,
+            Value::Bool(val) => rusqlite::types::Value::Integer(*val as i64),
+            Value::Int(val) => rusqlite::types::Value::Integer(*val),
+            Value::String(val) => rusqlite::types::Value::Text(val.clone()),
+        });
+
+        let mut statement = self.sqlite.prepare(sql)?;
+        let columns = statement.column_count();
+        let mut rows = statement.query(rusqlite::params_from_iter(params))?;
+        let mut result = Vec::new();
+        while let Some(row) = rows.next()? {
+            let mut values = Vec::with_capacity(columns);
+            for i in 0..columns {
+                values.push(sqlite_value(row.get_ref(i)?));
+            }
+            result.push(values);
+        }
+
+        Ok(sorted(result))
+    }
+}
+
+// sqlite has no BOOL type, true and false are 1 and 0
+fn normalize(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_owned(),
+        Value::Bool(val) => (*val as i64).to_string(),
+        Value::Int(val) => val.to_string(),
+        Value::String(val) => val.clone(),
+    }
+}
+
+// Value of sqlite result as text, also used by sqllogictest harness to record expected results
+pub fn sqlite_value(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_owned(),
+        ValueRef::Integer(val) => val.to_string(),
+        ValueRef::Real(val) => val.to_string(),
+        ValueRef::Text(val) => String::from_utf8_lossy(val).into_owned(),
+        ValueRef::Blob(val) => format!("{:?}", val),
+    }
+}
+
+// Queries without ORDER BY may return rows in any order
+fn sorted(mut rows: Rows) -> Rows {
+    rows.sort();
+    rows
+}
+
+// Generator of random queries over integer columns, same seed gives same queries
+pub struct Generator {
+    env: Env,
+    columns: Vec<String>,
+}
+
+impl Generator {
+    pub fn new(seed: u64, columns: &[&str]) -> Self {
+        Generator {
+            env: Env::simulated(seed),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    // Random number in 0..n
+    fn below(&self, n: u64) -> u64 {
+        self.env.random() as u64 % n
+    }
+
+    // Small integer, so that expressions don't overflow
+    pub fn int(&self) -> i64 {
+        self.below(201) as i64 - 100
+    }
+
+    // Values for INSERT INTO table VALUES (...)
+    pub fn row(&self) -> String {
+        let values: Vec<_> = self
+            .columns
+            .iter()
+            .map(|_| self.int().to_string())
+            .collect();
+        format!("({})", values.join(", "))
+    }
+
+    // SELECT over |table| and values for its placeholders
+    pub fn query(&self, table: &str) -> (String, Vec<Value>) {
+        let mut params = Vec::new();
+        let mut projection = Vec::new();
+        for _ in 0..=self.below(3) {
+            let e = match self.below(2) {
+                0 => self.int_expr(3, &mut params),
+                _ => self.bool_expr(3, &mut params),
+            };
+            projection.push(e);
+        }
+
+        let mut sql = format!("SELECT {} FROM {}", projection.join(", "), table);
+        if self.below(2) == 0 {
+            sql.push_str(" WHERE ");
+            sql.push_str(&self.bool_expr(3, &mut params));
+        }
+        (sql, params)
+    }
+
+    fn int_expr(&self, depth: u32, params: &mut Vec<Value>) -> String {
+        let leaf = depth == 0 || self.below(3) == 0;
+        if leaf {
+            return match self.below(3) {
+                0 => self.int().to_string(),
+                1 => {
+                    params.push(Value::Int(self.int()));
+                    "?".to_owned()
+                }
+                _ => self.columns[self.below(self.columns.len() as u64) as usize].clone(),
+            };
+        }
+
+        match self.below(6) {
+            0 => format!(
+                "({} + {})",
+                self.int_expr(depth - 1, params),
+                self.int_expr(depth - 1, params)
+            ),
+            1 => format!(
+                "({} - {})",
+                self.int_expr(depth - 1, params),
+                self.int_expr(depth - 1, params)
+            ),
+            2 => format!(
+                "({} * {})",
+                self.int_expr(depth - 1, params),
+                self.int_expr(depth - 1, params)
+            ),
+            // non-zero literal divisor
+            3 => format!(
+                "({} % {})",
+                self.int_expr(depth - 1, params),
+                self.below(10) + 1
+            ),
+            4 => format!("abs({})", self.int_expr(depth - 1, params)),
+            _ => format!(
+                "CASE WHEN {} THEN {} ELSE {} END",
+                self.bool_expr(depth - 1, params),
+                self.int_expr(depth - 1, params),
+                self.int_expr(depth - 1, params)
+            ),
+        }
+    }
+
+    fn bool_expr(&self, depth: u32, params: &mut Vec<Value>) -> String {
+        if depth == 0 {
+            return ["true", "false"][self.below(2) as usize].to_owned();
+        }
+
+        match self.below(4) {
+            0 => format!(
+                "({} AND {})",
+                self.bool_expr(depth - 1, params),
+                self.bool_expr(depth - 1, params)
+            ),
+            1 => format!(
+                "({} OR {})",
+                self.bool_expr(depth - 1, params),
+                self.bool_expr(depth - 1, params)
+            ),
+            2 => format!("(NOT {})", self.bool_expr(depth - 1, params)),
+            _ => {
+                let op = ["=", "!=", "<", "<=", ">", ">="][self.below(6) as usize];
+                format!(
+                    "({} {} {})",
+                    self.int_expr(depth - 1, params),
+                    op,
+                    self.int_expr(depth - 1, params)
+                )
+            }
+        }
+    }
+}
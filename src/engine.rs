@@ -1,77 +1,424 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+#[cfg(feature = "rocksdb")]
 use std::path::Path;
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
 
 use minitrace::trace;
-use rocksdb::{IteratorMode, Options, Transaction};
+use serde::{Deserialize, Serialize};
 use sqlparser::ast;
-use sqlparser::dialect::GenericDialect;
-use sqlparser::parser::Parser;
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::{Location, Token, TokenWithLocation, Tokenizer};
 
+use crate::access::{Access, AccessHook};
+use crate::dialect::Dialect;
+use crate::env::{self, Env};
+use crate::error::Error;
 use crate::expression::Expression;
-use crate::ops::{self, Empty as EmptySource, Eval, Filter, FullScan, Operation, Sort, Values};
-use crate::schema::{Column, Schema};
+use crate::ops::{
+    self, Aggregate, AggregateCall, AggregateFunction, Empty as EmptySource, Eval, Filter,
+    FullScan, FunctionScan, Limit, LimitBy, Operation, Plan, Sort, Values,
+};
+use crate::schema::{normalize_ident, Column, LegacySchema, Schema, Type};
+use crate::storage::{self, Direction, Storage, Transaction, CATALOG};
 use crate::table::Table;
-use crate::types::{Database, Result, Row, RowSet};
+use crate::table_function::{self, TableFunction};
+use crate::types::{Result, Row, RowSet, Value};
 
-type ColumnFamily<'db> = Arc<rocksdb::BoundColumnFamily<'db>>;
+// Tables with this prefix are maintained by the engine and can't be created, dropped or modified
+const SYSTEM_PREFIX: &str = "camellia_";
+// Statements executed by the engine, see EngineBuilder::audit_log
+const AUDIT_LOG: &str = "camellia_audit_log";
+// Column family with approximate row count of each table, not a part of catalog
+const ROW_COUNTS: &str = "camellia_row_counts";
+// Catalog key of the last assigned generation, table names are never empty
+const GENERATION_KEY: &[u8] = b"";
+// First byte of catalog entries, bump when layout of CatalogEntry or Schema changes.
+// Entries written before it are bare LegacySchema, which starts with Option tag (0 or 1)
+const CATALOG_FORMAT: u8 = 2;
+
+// Value of catalog entry, keyed by table name
+#[derive(Serialize, Deserialize)]
+struct CatalogEntry {
+    // unique among all tables ever created in the database, so that cached Table
+    // can be checked against catalog which was changed by another Engine instance.
+    // 0 for entries written before generations, see CATALOG_FORMAT
+    // NOTE: must stay the first field, see Engine::read_generation
+    generation: u64,
+    schema: Schema,
+}
 
 pub enum Output {
     Rows(RowSet),
     Affected(usize),
 }
 
+// Wall time spent in each phase of statement execution
+#[derive(Debug, Default, Clone)]
+pub struct Stats {
+    pub parse: Duration,
+    pub plan: Duration,
+    pub execute: Duration,
+    // Rows returned or affected
+    pub rows: usize,
+
+    // None => timings are not collected
+    // NOTE: Instant::now() panics on wasm32-unknown-unknown
+    last: Option<Instant>,
+}
+
+impl Stats {
+    pub(crate) fn timed() -> Self {
+        Stats {
+            last: Some(Instant::now()),
+            ..Stats::default()
+        }
+    }
+
+    // Time since previous lap
+    pub(crate) fn lap(&mut self) -> Duration {
+        match &mut self.last {
+            Some(last) => {
+                let now = Instant::now();
+                let elapsed = now - *last;
+                *last = now;
+                elapsed
+            }
+            None => Duration::ZERO,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct EngineBuilder {
+    readonly: bool,
+    audit_log: bool,
+    dialect: Dialect,
+    access_hooks: Vec<Arc<dyn AccessHook>>,
+    table_functions: HashMap<String, Arc<dyn TableFunction>>,
+    // None => Env::system()
+    env: Option<Env>,
+}
+
+impl EngineBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Reject all statements except queries
+    pub fn readonly(mut self, on: bool) -> Self {
+        self.readonly = on;
+        self
+    }
+
+    // Record every executed statement with its timestamp, duration, number of rows and error
+    // into camellia_audit_log table, e.g. "SELECT * FROM camellia_audit_log"
+    // NOTE: takes time from Env, system clock is not available on wasm32-unknown-unknown
+    pub fn audit_log(mut self, on: bool) -> Self {
+        self.audit_log = on;
+        self
+    }
+
+    // Dialect of run_sql and of new sessions, which may change it with SET dialect = '...'
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    // Consult |hook| on every access to a table, see AccessHook.
+    // Several hooks may be added, all of them must allow the access
+    pub fn access_hook(mut self, hook: Arc<dyn AccessHook>) -> Self {
+        self.access_hooks.push(hook);
+        self
+    }
+
+    // Make |function| available as SELECT * FROM name(args), replaces function with the same name
+    pub fn table_function(mut self, name: &str, function: Arc<dyn TableFunction>) -> Self {
+        self.table_functions
+            .insert(name.to_ascii_lowercase(), function);
+        self
+    }
+
+    // Open (or create) rocksdb-backed database at |path|
+    #[cfg(feature = "rocksdb")]
+    pub fn open<P: AsRef<Path>>(self, path: P) -> Result<Engine> {
+        let storage = storage::RocksDb::open(path.as_ref())?;
+        Ok(self.build(Box::new(storage)))
+    }
+
+    // Create database which lives only as long as the engine
+    pub fn in_memory(self) -> Engine {
+        self.build(Box::new(storage::Memory::new()))
+    }
+
+    // In-memory database with seeded random() and frozen clock, see camellia::Env
+    pub fn simulated(mut self, seed: u64) -> Engine {
+        self.env = Some(Env::simulated(seed));
+        self.in_memory()
+    }
+
+    fn build(self, storage: Box<dyn Storage>) -> Engine {
+        let mut table_functions = table_function::builtins();
+        table_functions.extend(self.table_functions);
+        Engine {
+            storage,
+            env: Arc::new(self.env.unwrap_or_else(Env::system)),
+            readonly: self.readonly,
+            audit_log: self.audit_log,
+            dialect: self.dialect,
+            access_hooks: self.access_hooks,
+            table_functions,
+            tables: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
 pub struct Engine {
-    db: Database,
-    log: AtomicBool,
+    storage: Box<dyn Storage>,
+    env: Arc<Env>,
+    readonly: bool,
+    audit_log: bool,
+    // sessions start with it, but keep their own, see Session::set_dialect
+    dialect: Dialect,
+    access_hooks: Vec<Arc<dyn AccessHook>>,
+    table_functions: HashMap<String, Arc<dyn TableFunction>>,
 
     tables: RwLock<HashMap<String, Arc<Table>>>,
 }
 
 impl Engine {
+    pub fn builder() -> EngineBuilder {
+        EngineBuilder::new()
+    }
+
+    #[cfg(feature = "rocksdb")]
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let path = path.as_ref();
-        let mut opts = Options::default();
-        opts.create_if_missing(true);
-        let txn_db_opts = rocksdb::TransactionDBOptions::default();
-        let column_families = if path.exists() {
-            Database::list_cf(&opts, path)?
-        } else {
-            Vec::new()
-        };
-        let db = Database::open_cf(&opts, &txn_db_opts, path, column_families)?;
-        let tables = RwLock::new(HashMap::new());
-        Ok(Engine {
-            db,
-            tables,
-            log: AtomicBool::new(false),
-        })
+        Engine::builder().open(path)
+    }
+
+    pub fn in_memory() -> Self {
+        Engine::builder().in_memory()
     }
 
-    pub fn set_log(&self, on: bool) {
-        self.log.store(on, Ordering::Relaxed);
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
     }
 
     pub fn run_sql(&self, program: &str) -> Result<Output> {
-        let dialect = GenericDialect {};
-        let program = Parser::parse_sql(&dialect, program)?;
+        let program = self.parse(program)?;
+        self.run(program)
+    }
+
+    // Same as run_sql, but substitutes ? and $N placeholders with |params|
+    pub fn run_sql_with_params(&self, program: &str, params: &[Value]) -> Result<Output> {
+        let mut program = self.parse(program)?;
+        bind(&mut program, params)?;
         self.run(program)
     }
 
+    // Names of all tables in the database
+    pub fn tables(&self) -> Result<Vec<String>> {
+        let transaction = self.storage.transaction();
+        let mut tables = Vec::new();
+        for entry in transaction.iter(CATALOG, Direction::Forward)? {
+            let (name, _entry) = entry?;
+            if &*name == GENERATION_KEY {
+                continue;
+            }
+
+            tables.push(String::from_utf8(name.into_vec())?);
+        }
+
+        Ok(tables)
+    }
+
+    pub fn schema(&self, table: &str) -> Result<Schema> {
+        if !self.storage.has_cf(table) {
+            return Err("No such table".into());
+        }
+
+        let transaction = self.storage.transaction();
+        let table = self.get_table(table.to_owned(), &*transaction)?;
+        Ok(table.schema().clone())
+    }
+
+    // Same as run_sql, but also reports time spent in parse, plan and execute phases
+    pub fn run_sql_with_stats(&self, program: &str) -> Result<(Output, Stats)> {
+        let mut stats = Stats::timed();
+        let program = self.parse(program)?;
+        stats.parse = stats.lap();
+        let output = self.run_with_stats(program, &mut stats)?;
+        Ok((output, stats))
+    }
+
+    pub(crate) fn parse(&self, program: &str) -> Result<Vec<ast::Statement>> {
+        self.parse_with_dialect(program, self.dialect)
+    }
+
+    pub(crate) fn parse_with_dialect(
+        &self,
+        program: &str,
+        dialect: Dialect,
+    ) -> Result<Vec<ast::Statement>> {
+        let dialect = dialect.parser_dialect();
+        let tokens = Tokenizer::new(dialect, program)
+            .tokenize_with_location()
+            .map_err(|e| Error::Parse {
+                message: e.message,
+                line: e.location.line,
+                column: e.location.column,
+                token: None,
+            })?;
+
+        // X'FF' and 0xFF are both tokenized as hex string, only the latter is supported
+        let end = end_location(program);
+        let blob = tokens.iter().enumerate().find(|(i, token)| {
+            let next = tokens.get(i + 1).map_or(end, |next| next.location);
+            is_blob_literal(token, next)
+        });
+        if let Some((_, blob)) = blob {
+            return Err(Error::Parse {
+                message: "Blob literals are not supported".to_owned(),
+                line: blob.location.line,
+                column: blob.location.column,
+                token: Some(blob.token.to_string()),
+            }
+            .into());
+        }
+
+        let mut parser = Parser::new(dialect).with_tokens_with_locations(tokens);
+        match parser.parse_statements() {
+            Ok(program) => Ok(program),
+            Err(e) => Err(parse_error(program, e, parser.peek_token()).into()),
+        }
+    }
+
+    // Plan of a single query, see Plan
+    pub fn explain(&self, query: &str) -> Result<Plan> {
+        let program = self.parse(query)?;
+        let query = match <[ast::Statement; 1]>::try_from(program) {
+            Ok([ast::Statement::Query(query)]) => query,
+            _ => return Err("Expected single query".into()),
+        };
+
+        let _env = env::enter(self.env.clone());
+        self.plan(*query)
+    }
+
     pub fn run(&self, program: Vec<ast::Statement>) -> Result<Output> {
+        let mut stats = Stats::default();
+        self.run_with_stats(program, &mut stats)
+    }
+
+    pub(crate) fn run_with_stats(
+        &self,
+        program: Vec<ast::Statement>,
+        stats: &mut Stats,
+    ) -> Result<Output> {
         if program.len() != 1 {
             return Err("Cannot run more than one statement at time".into());
         }
 
         let statement = program.into_iter().next().unwrap();
-        if self.log.load(Ordering::Relaxed) {
-            println!("{:#?}", statement);
+        let _env = env::enter(self.env.clone());
+        let span = crate::trace::root("query", || statement.to_string());
+        let _guard = span.set_local_parent();
+        log::debug!(target: "camellia::plan", "{:#?}", statement);
+
+        if !self.audit_log {
+            return self.execute_checked(statement, stats);
+        }
+
+        let sql = statement.to_string();
+        // wall clock may go backwards and is frozen in simulated Env, so that
+        // both timestamp and duration are reproducible there
+        let timestamp = self.env.now();
+        let output = self.execute_checked(statement, stats);
+        let duration = self.env.now().duration_since(timestamp).unwrap_or_default();
+
+        let (rows, error) = match &output {
+            Ok(Output::Rows(rowset)) => (rowset.rows.len(), None),
+            Ok(Output::Affected(n)) => (*n, None),
+            Err(e) => (0, Some(e.to_string())),
+        };
+        let timestamp = timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        let row = Row::from(vec![
+            Value::String(sql),
+            Value::Int(timestamp.as_millis() as i64),
+            Value::Int(duration.as_micros() as i64),
+            Value::Int(rows as i64),
+            error.map_or(Value::Null, Value::String),
+        ]);
+
+        match self.write_audit_log(row) {
+            Ok(()) => output,
+            // statement itself is already committed at this point
+            Err(e) if output.is_ok() => {
+                Err(format!("Statement executed, but failed to write audit log: {}", e).into())
+            }
+            Err(_) => output,
         }
-        self.execute(statement)
     }
 
-    fn execute(&self, statement: ast::Statement) -> Result<Output> {
+    fn execute_checked(&self, statement: ast::Statement, stats: &mut Stats) -> Result<Output> {
+        let is_read = matches!(
+            statement,
+            ast::Statement::Query(_)
+                | ast::Statement::Explain { .. }
+                | ast::Statement::ShowVariable { .. }
+        );
+        if self.readonly && !is_read {
+            return Err("Database is opened in read-only mode".into());
+        }
+        self.execute(statement, stats)
+    }
+
+    #[trace]
+    fn write_audit_log(&self, row: Row) -> Result<()> {
+        if !self.storage.has_cf(AUDIT_LOG) {
+            let column = |name: &str, type_| Column {
+                name: name.to_owned(),
+                type_,
+            };
+            let schema = Schema {
+                primary_key: None,
+                strict: false,
+                columns: vec![
+                    column("sql", Type::Text),
+                    // unix time in milliseconds
+                    column("timestamp", Type::Integer),
+                    column("duration_us", Type::Integer),
+                    column("rows", Type::Integer),
+                    // null if statement succeeded
+                    column("error", Type::Text),
+                ],
+            };
+            self.create_table(AUDIT_LOG, &schema)?;
+        }
+
+        let transaction = self.storage.transaction();
+        let table = self.get_table(AUDIT_LOG.to_owned(), &*transaction)?;
+        let mut key = Vec::new();
+        let mut value = Vec::new();
+        table.get_key(&row, &mut key)?;
+        row.serialize(&mut value)?;
+        transaction.put(AUDIT_LOG, &key, &value)?;
+        transaction.commit()?;
+        log::trace!(target: "camellia::txn", "committed audit log entry");
+        if table.add_rows(1) {
+            self.persist_row_count(AUDIT_LOG, &table)?;
+        }
+        Ok(())
+    }
+
+    fn execute(&self, statement: ast::Statement, stats: &mut Stats) -> Result<Output> {
         match statement {
             ast::Statement::CreateTable {
                 name,
@@ -106,12 +453,13 @@ impl Engine {
                 on_commit: None,
                 on_cluster: None,
                 order_by: None,
-                strict: false,
+                strict,
             } if constraints.is_empty()
                 && table_properties.is_empty()
                 && with_options.is_empty() =>
             {
-                self.create(name, columns)?;
+                self.create(name, columns, strict)?;
+                stats.execute = stats.lap();
                 Ok(Output::Affected(0))
             }
             ast::Statement::Drop {
@@ -125,12 +473,58 @@ impl Engine {
             } if names.len() == 1 => {
                 let name = names.into_iter().next().unwrap();
                 self.drop(name)?;
+                stats.execute = stats.lap();
                 Ok(Output::Affected(0))
             }
             ast::Statement::Query(query) => {
-                let rows = self.query(*query)?;
+                let rows = self.query(*query, stats)?;
                 Ok(rows)
             }
+            ast::Statement::Explain {
+                statement,
+                analyze: false,
+                format: None,
+                ..
+            } => {
+                let plan = match *statement {
+                    ast::Statement::Query(query) => self.plan(*query)?,
+                    _ => return Err("Only queries can be explained".into()),
+                };
+                stats.plan = stats.lap();
+
+                let rowset = RowSet {
+                    rows: plan
+                        .to_string()
+                        .lines()
+                        .map(|line| Row::from(vec![Value::String(line.to_owned())]))
+                        .collect(),
+                    schema: Schema {
+                        primary_key: None,
+                        strict: false,
+                        columns: vec![Column {
+                            name: "plan".to_owned(),
+                            type_: Type::Text,
+                        }],
+                    },
+                };
+                stats.rows = rowset.rows.len();
+                Ok(Output::Rows(rowset))
+            }
+            // SHOW STATUS
+            ast::Statement::ShowVariable { variable }
+                if variable.len() == 1 && variable[0].value.eq_ignore_ascii_case("status") =>
+            {
+                let rowset = self.status()?;
+                stats.execute = stats.lap();
+                stats.rows = rowset.rows.len();
+                Ok(Output::Rows(rowset))
+            }
+            // dialect is state of the client, see Session::execute
+            ast::Statement::SetVariable { variable, .. }
+                if variable.to_string().eq_ignore_ascii_case("dialect") =>
+            {
+                Err("SET dialect is only supported by sessions, e.g. REPL".into())
+            }
             ast::Statement::Insert {
                 or: None,
                 ignore: false,
@@ -145,25 +539,37 @@ impl Engine {
                 on: None,
                 returning: None,
             } if after_columns.is_empty() => {
-                let n = self.insert(table_name, columns, *source)?;
+                let n = self.insert(table_name, columns, *source, stats)?;
                 Ok(Output::Affected(n))
             }
             _ => Err("Not supported".into()),
         }
     }
 
+    // Build operator tree of |query| without executing it, same as EXPLAIN
     #[trace]
-    fn query(&self, query: ast::Query) -> Result<Output> {
-        let transaction = self.db.transaction();
-        let mut source = self.build_query(query, &transaction)?;
+    fn plan(&self, query: ast::Query) -> Result<Plan> {
+        let transaction = self.storage.transaction();
+        let source = self.build_query(query, &*transaction)?;
+        Ok(source.explain())
+    }
+
+    #[trace]
+    fn query(&self, query: ast::Query, stats: &mut Stats) -> Result<Output> {
+        let transaction = self.storage.transaction();
+        let mut source = self.build_query(query, &*transaction)?;
+        stats.plan = stats.lap();
+
         let mut rows = Vec::new();
         loop {
             match source.poll() {
                 Ok(ops::Output::Finished) => {
+                    stats.execute = stats.lap();
+                    stats.rows = rows.len();
                     break Ok(Output::Rows(RowSet {
                         rows,
                         schema: source.schema().clone(),
-                    }))
+                    }));
                 }
                 Ok(ops::Output::Batch(mut batch)) => {
                     rows.append(&mut batch);
@@ -173,36 +579,138 @@ impl Engine {
         }
     }
 
+    // Engine counters and storage properties of each table as (name, value) rows
     #[trace]
-    fn create(&self, name: ast::ObjectName, columns: Vec<ast::ColumnDef>) -> Result<()> {
-        let table = name.to_string();
+    fn status(&self) -> Result<RowSet> {
+        // tables which access hooks hide, fully or partially, are not reported at all
+        let all = self.tables()?;
+        let tables: Vec<String> = all
+            .iter()
+            .filter(|table| self.is_visible(table))
+            .cloned()
+            .collect();
+        // tables with cached schema
+        let open_tables = {
+            let cache = self.tables.read().unwrap();
+            tables
+                .iter()
+                .filter(|table| cache.contains_key(*table))
+                .count()
+        };
+        let mut status = vec![
+            ("tables".to_owned(), tables.len() as u64),
+            ("open_tables".to_owned(), open_tables as u64),
+        ];
+
+        // catalog properties cover hidden tables too
+        if tables.len() == all.len() {
+            for (name, value) in self.storage.properties(CATALOG)? {
+                status.push((format!("catalog.{}", name), value));
+            }
+        }
+        let transaction = self.storage.transaction();
+        for table in &tables {
+            let rows = self.get_table(table.clone(), &*transaction)?.estimated_rows();
+            status.push((format!("{}.rows", table), rows));
+            for (name, value) in self.storage.properties(table)? {
+                status.push((format!("{}.{}", table, name), value));
+            }
+        }
+
+        let schema = Schema {
+            primary_key: None,
+            strict: false,
+            columns: vec![
+                Column {
+                    name: "name".to_owned(),
+                    type_: Type::Text,
+                },
+                Column {
+                    name: "value".to_owned(),
+                    type_: Type::Integer,
+                },
+            ],
+        };
+        let rows = status
+            .into_iter()
+            .map(|(name, value)| Row::from(vec![Value::String(name), Value::Int(value as i64)]))
+            .collect();
+
+        Ok(RowSet { schema, rows })
+    }
+
+    #[trace]
+    fn create(
+        &self,
+        name: ast::ObjectName,
+        columns: Vec<ast::ColumnDef>,
+        strict: bool,
+    ) -> Result<()> {
+        let table = table_name(&name)?;
+        check_not_system(&table)?;
+        self.check_access(&table, Access::Create)?;
+        if table.is_empty() {
+            return Err("Table name cannot be empty".into());
+        }
+
+        // name of column family which holds the catalog itself
+        if table == CATALOG {
+            return Err(format!("Table name {} is reserved", table).into());
+        }
+
+        if self.storage.has_cf(&table) {
+            return Err(format!("Table {} already exists", table).into());
+        }
+
         for column in &columns {
             crate::types::type_of(column)?;
         }
 
-        let opts = Options::default();
-        self.db.create_cf(&table, &opts)?;
+        let mut schema = Schema::new(columns)?;
+        schema.strict = strict;
+        self.create_table(&table, &schema)
+    }
+
+    fn create_table(&self, table: &str, schema: &Schema) -> Result<()> {
+        self.storage.create_cf(table)?;
 
-        let transaction = self.db.transaction();
-        if transaction.get(&table)?.is_some() {
+        let transaction = self.storage.transaction();
+        if transaction.get(CATALOG, table.as_bytes())?.is_some() {
             return Err("Table with such name already exist, but shouldn't".into());
         }
 
-        let schema = Schema::new(columns)?;
-        let schema = bincode::serialize(&schema)?;
-        transaction.put(&table, schema)?;
+        let generation = match transaction.get_for_update(CATALOG, GENERATION_KEY)? {
+            Some(bytes) => bincode::deserialize::<u64>(&bytes)? + 1,
+            None => 1,
+        };
+        transaction.put(CATALOG, GENERATION_KEY, &bincode::serialize(&generation)?)?;
+
+        let entry = CatalogEntry {
+            generation,
+            schema: schema.clone(),
+        };
+        let mut bytes = vec![CATALOG_FORMAT];
+        bincode::serialize_into(&mut bytes, &entry)?;
+        transaction.put(CATALOG, table.as_bytes(), &bytes)?;
         transaction.commit()?;
+        log::info!(target: "camellia::storage", "created table {}", table);
         Ok(())
     }
 
     #[trace]
     fn drop(&self, name: ast::ObjectName) -> Result<()> {
-        let table = name.to_string();
-        let transaction = self.db.transaction();
-        transaction.delete(&table)?;
+        let table = table_name(&name)?;
+        check_not_system(&table)?;
+        self.check_access(&table, Access::Drop)?;
+        let transaction = self.storage.transaction();
+        transaction.delete(CATALOG, table.as_bytes())?;
+        if self.storage.has_cf(ROW_COUNTS) {
+            transaction.delete(ROW_COUNTS, table.as_bytes())?;
+        }
         transaction.commit()?;
-        self.db.drop_cf(&table)?;
+        self.storage.drop_cf(&table)?;
         self.tables.write().unwrap().remove(&table);
+        log::info!(target: "camellia::storage", "dropped table {}", table);
         Ok(())
     }
 
@@ -212,14 +720,19 @@ impl Engine {
         name: ast::ObjectName,
         _columns: Vec<ast::Ident>,
         source: ast::Query,
+        stats: &mut Stats,
     ) -> Result<usize> {
-        let table = name.to_string();
-        let cf = self.db.cf_handle(&table).ok_or("No such table")?;
+        let name = table_name(&name)?;
+        check_not_system(&name)?;
+        if !self.storage.has_cf(&name) {
+            return Err("No such table".into());
+        }
+        self.check_access(&name, Access::Insert)?;
 
-        let transaction = self.db.transaction();
-        let mut source = self.build_query(source, &transaction)?;
+        let transaction = self.storage.transaction();
+        let mut source = self.build_query(source, &*transaction)?;
 
-        let table = self.get_table(table, &cf, &transaction)?;
+        let table = self.get_table(name.clone(), &*transaction)?;
         let schema = table.schema();
         // TODO: support column reordering
         // if !columns.is_empty() {
@@ -228,29 +741,50 @@ impl Engine {
 
         // Check that source stream matches table schema
         schema.check_compatible(source.schema())?;
+        let policy = match self.access_predicate(&name, Access::Insert)? {
+            Some(predicate) => Some(Expression::parse(predicate, schema)?),
+            None => None,
+        };
+        stats.plan = stats.lap();
         let mut n_rows = 0;
 
-        let mut key = Vec::new();
-        let mut value = Vec::new();
         loop {
             match source.poll() {
                 Ok(ops::Output::Finished) => {
                     break;
                 }
                 Ok(ops::Output::Batch(batch)) => {
+                    // Uniqueness is checked and rows are written once per batch
+                    let mut entries = Vec::with_capacity(batch.len());
                     for row in batch {
-                        key.clear();
-                        value.clear();
-
-                        table.get_key(&row, &mut key);
-                        if transaction.get_for_update_cf(&cf, &key, true)?.is_some() {
-                            return Err("Entry with such primary key already exist".into());
+                        let mut key = Vec::new();
+                        let mut value = Vec::new();
+                        let row = schema.coerce(row)?;
+                        let allowed = match &policy {
+                            Some(policy) => policy.eval(&row)?.to_truth() == Some(Some(true)),
+                            None => true,
+                        };
+                        if !allowed {
+                            return Err(
+                                format!("Row violates access policy of table {}", name).into()
+                            );
                         }
-
+                        table.get_key(&row, &mut key)?;
                         row.serialize(&mut value)?;
-                        transaction.put_cf(&cf, &key, &value)?;
-                        n_rows += 1;
+                        entries.push((key.into_boxed_slice(), value.into_boxed_slice()));
                     }
+
+                    let keys: Vec<&[u8]> = entries.iter().map(|(key, _)| &**key).collect();
+                    let mut unique = HashSet::with_capacity(keys.len());
+                    let existing = transaction.multi_get_for_update(&name, &keys)?;
+                    if !keys.iter().all(|key| unique.insert(*key))
+                        || existing.iter().any(Option::is_some)
+                    {
+                        return Err("Entry with such primary key already exist".into());
+                    }
+
+                    transaction.put_batch(&name, &entries)?;
+                    n_rows += entries.len();
                 }
                 Err(e) => return Err(e),
             }
@@ -258,44 +792,93 @@ impl Engine {
 
         drop(source);
         transaction.commit()?;
+        log::debug!(target: "camellia::txn", "committed {} row(s) into {}", n_rows, name);
+        if table.add_rows(n_rows as u64) {
+            self.persist_row_count(&name, &table)?;
+        }
+        stats.execute = stats.lap();
+        stats.rows = n_rows;
         Ok(n_rows)
     }
 
     fn build_query<'txn>(
         &self,
         query: ast::Query,
-        transaction: &'txn Transaction<'_, Database>,
+        transaction: &'txn dyn Transaction,
     ) -> Result<Box<dyn Operation + 'txn>> {
-        let (query, order_by) = match query {
+        let (query, order_by, limit, limit_by, offset, fetch) = match query {
             ast::Query {
                 with: None,
                 body,
                 order_by,
-                limit: None,
+                limit,
                 limit_by,
-                offset: None,
-                fetch: None,
+                offset,
+                fetch,
                 locks,
                 for_clause: None,
-            } if limit_by.is_empty() && locks.is_empty() => (*body, order_by),
+            } if locks.is_empty() => (*body, order_by, limit, limit_by, offset, fetch),
             _ => return Err("Not implemented".into()),
         };
 
-        match query {
-            ast::SetExpr::Select(select) => self.build_select(*select, order_by, transaction),
+        // FETCH FIRST n ROWS ONLY is the standard form of LIMIT n
+        let limit = match (limit, fetch) {
+            (limit, None) => limit.map(|e| row_count(e, "LIMIT")).transpose()?.flatten(),
+            (
+                None,
+                Some(ast::Fetch {
+                    with_ties: false,
+                    percent: false,
+                    quantity,
+                }),
+            ) => match quantity {
+                Some(e) => row_count(e, "FETCH")?,
+                None => Some(1),
+            },
+            _ => return Err("Unsupported FETCH clause".into()),
+        };
+        let offset = match offset {
+            Some(offset) => row_count(offset.value, "OFFSET")?.unwrap_or(0),
+            None => 0,
+        };
+
+        // LIMIT n OFFSET m BY a, b: at most n rows per (a, b) after skipping m of them,
+        // applied by build_select
+        if !limit_by.is_empty() {
+            let limit = limit.ok_or("LIMIT BY requires a row count")?;
+            return match query {
+                ast::SetExpr::Select(select) => {
+                    let limit_by = Some((limit, offset, limit_by));
+                    self.build_select(*select, order_by, limit_by, transaction)
+                }
+                _ => Err("LIMIT BY is only supported for SELECT".into()),
+            };
+        }
+
+        let mut source = match query {
+            ast::SetExpr::Select(select) => {
+                self.build_select(*select, order_by, None, transaction)?
+            }
             // TODO: support order_by for values
-            ast::SetExpr::Values(values) if order_by.is_empty() => self.build_values(values),
-            _ => Err("Unsupported query kind".into()),
+            ast::SetExpr::Values(values) if order_by.is_empty() => self.build_values(values)?,
+            _ => return Err("Unsupported query kind".into()),
+        };
+
+        if limit.is_some() || offset > 0 {
+            source = Box::new(Limit::new(offset, limit, source));
         }
+        Ok(source)
     }
 
     fn build_select<'txn>(
         &self,
         query: ast::Select,
-        order_by: Vec<ast::OrderByExpr>,
-        transaction: &'txn Transaction<'_, Database>,
+        mut order_by: Vec<ast::OrderByExpr>,
+        // (limit, offset, terms) of LIMIT BY
+        limit_by: Option<(usize, usize, Vec<ast::Expr>)>,
+        transaction: &'txn dyn Transaction,
     ) -> Result<Box<dyn Operation + 'txn>> {
-        let (table, expressions, where_) = match query {
+        let (table, mut expressions, mut where_, mut group_by, mut having) = match query {
             ast::Select {
                 distinct: None,
                 top: None,
@@ -308,12 +891,11 @@ impl Engine {
                 cluster_by,
                 distribute_by,
                 sort_by,
-                having: None,
+                having,
                 named_window,
                 qualify: None,
             } if from.len() <= 1
                 && lateral_views.is_empty()
-                && group_by_exprs.is_empty()
                 && cluster_by.is_empty()
                 && distribute_by.is_empty()
                 && sort_by.is_empty()
@@ -324,48 +906,106 @@ impl Engine {
                         relation:
                             ast::TableFactor::Table {
                                 name,
-                                alias: None,
-                                args: None,
+                                alias,
+                                args,
                                 with_hints,
                                 version: None,
                                 partitions,
                             },
                         joins,
-                    }) if joins.is_empty() && with_hints.is_empty() && partitions.is_empty() => {
-                        Some(name.to_string())
+                    }) if joins.is_empty()
+                        && with_hints.is_empty()
+                        && partitions.is_empty()
+                        && alias.iter().all(|alias| alias.columns.is_empty()) =>
+                    {
+                        let table = table_name(&name)?;
+                        // name the table is referred by in the rest of the query
+                        let relation = match alias {
+                            Some(alias) => normalize_ident(&alias.name),
+                            None => table.clone(),
+                        };
+                        Some((table, relation, args))
                     }
                     None => None,
                     _ => return Err("Unsupported select source".into()),
                 };
 
-                (name, projection, selection)
+                (name, projection, selection, group_by_exprs, having)
             }
             _ => return Err("Unsupported select kind".into()),
         };
 
+        let relation = table.as_ref().map(|(_, relation, _)| relation.clone());
+        resolve_qualified(&mut expressions, relation.as_deref())?;
+        resolve_qualified(&mut where_, relation.as_deref())?;
+        resolve_qualified(&mut group_by, relation.as_deref())?;
+        resolve_qualified(&mut having, relation.as_deref())?;
+        resolve_qualified(&mut order_by, relation.as_deref())?;
+
         let mut source = match table {
-            Some(table) => {
-                let cf = self.db.cf_handle(&table).ok_or("No such table")?;
-                let table = self.get_table(table, &cf, transaction)?;
+            // table function, e.g. generate_series(1, 10)
+            Some((name, _, Some(args))) => self.call_table_function(name, args)?,
+            Some((name, _, None)) => {
+                if !self.storage.has_cf(&name) {
+                    return Err("No such table".into());
+                }
+                self.check_access(&name, Access::Read)?;
+
+                let table = self.get_table(name.clone(), transaction)?;
                 let schema = table.schema().clone();
+                let rows = table.estimated_rows();
 
-                let iter = transaction.iterator_cf(&cf, IteratorMode::Start);
-                Box::new(FullScan::new(schema, iter)?) as Box<dyn Operation>
+                let iter = transaction.iter(&name, Direction::Forward)?;
+                let predicate = self.access_predicate(&name, Access::Read)?;
+                let mut scan: Box<dyn Operation> =
+                    Box::new(FullScan::new(name, schema, rows, iter)?);
+                // rows hidden by access hooks are filtered before anything else
+                if let Some(predicate) = predicate {
+                    scan = Box::new(Filter::new(predicate, scan)?);
+                }
+                scan
             }
             None => Box::new(EmptySource::new()) as Box<dyn Operation>,
         };
 
-        if let Some(where_) = where_ {
+        if let Some(mut where_) = where_ {
+            resolve_aliases(&mut where_, &expressions, source.schema());
             let filter = Filter::new(where_, source)?;
             source = Box::new(filter)
         }
 
+        let aggregated = !group_by.is_empty()
+            || having.is_some()
+            || has_aggregates(&expressions)
+            || has_aggregates(&order_by);
+        if aggregated {
+            source = build_aggregate(group_by, having, &mut expressions, &mut order_by, source)?;
+        }
+
+        // LIMIT BY terms may refer to columns which are not projected, same as ORDER BY
+        let limit_by = match limit_by {
+            Some((limit, offset, terms)) => {
+                let mut by = Vec::with_capacity(terms.len());
+                for mut term in terms {
+                    resolve_qualified(&mut term, relation.as_deref())?;
+                    resolve_aliases(&mut term, &expressions, source.schema());
+                    by.push(Expression::parse(term, source.schema())?);
+                }
+                Some((limit, offset, by))
+            }
+            None => None,
+        };
+
         // NOTE: this code expects that Sort operator does not alter row stream Schema, i.e. sort.schema() == source.schema()
-        let (schema, expressions) = expand_select(expressions, source.schema())?;
+        let (schema, expressions) =
+            expand_select(expressions, relation.as_deref(), source.schema())?;
         if !order_by.is_empty() {
             let sort = Sort::new(order_by, &expressions, source)?;
             source = Box::new(sort);
         }
+        if let Some((limit, offset, by)) = limit_by {
+            source = Box::new(LimitBy::new(limit, offset, by, source));
+        }
 
         let source = Eval::new(expressions, schema, source)?;
         Ok(Box::new(source))
@@ -385,115 +1025,673 @@ impl Engine {
             Ok(Row::from(values))
         }
 
-        let mut rows = values.rows.into_iter();
-        // get first row to infer schema
-        let first = match rows.next() {
-            Some(exprs) => build_row(exprs)?,
-            None => {
-                // empty values
-                let values = Values::new(Vec::new(), Schema::empty())?;
-                return Ok(Box::new(values));
-            }
+        let mut rows = Vec::with_capacity(values.rows.len());
+        for exprs in values.rows {
+            rows.push(build_row(exprs)?);
+        }
+
+        // every column gets the common type of its values, e.g. NULL and 1 => INT
+        let mut types: Vec<Type> = match rows.first() {
+            Some(first) => first.values().map(Value::type_).collect(),
+            // empty values
+            None => Vec::new(),
         };
+        for row in rows.iter().skip(1) {
+            if row.len() != types.len() {
+                return Err(format!(
+                    "All VALUES must have the same number of terms: expected {} but got {}",
+                    types.len(),
+                    row.len()
+                )
+                .into());
+            }
+
+            for (i, value) in row.values().enumerate() {
+                let type_ = value.type_();
+                types[i] = types[i].unify(type_).ok_or_else(|| {
+                    format!(
+                        "column{} of VALUES has incompatible types: {} and {}",
+                        i + 1,
+                        types[i],
+                        type_
+                    )
+                })?;
+            }
+        }
 
         let schema = Schema {
             primary_key: None,
-            columns: first
-                .values()
+            strict: false,
+            columns: types
+                .iter()
                 .enumerate()
-                .map(|(i, val)| Column {
-                    name: format!("column{}", i+1),
-                    type_: val.type_(),
+                .map(|(i, type_)| Column {
+                    name: format!("column{}", i + 1),
+                    type_: *type_,
                 })
                 .collect(),
         };
 
-        let mut values = Vec::with_capacity(rows.len() + 1);
-        values.push(first);
-
-        for exprs in rows {
-            let row = build_row(exprs)?;
-            schema.check(&row)?;
-            values.push(row);
+        let mut values = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut converted = Vec::with_capacity(types.len());
+            for (value, type_) in row.into_values().zip(&types) {
+                // unify guarantees that conversion succeeds
+                converted.push(value.convert(*type_).ok_or("Invalid VALUES conversion")?);
+            }
+            values.push(Row::from(converted));
         }
 
         let values = Values::new(values, schema)?;
         Ok(Box::new(values))
     }
 
+    fn call_table_function(
+        &self,
+        name: String,
+        args: Vec<ast::FunctionArg>,
+    ) -> Result<Box<dyn Operation>> {
+        let function = self
+            .table_functions
+            .get(&name)
+            .ok_or_else(|| format!("No such table function: {}", name))?;
+        // functions may read files etc., so hooks see them like tables
+        self.check_access(&name, Access::Read)?;
+
+        let schema = Schema::empty();
+        let row = Row::from(Vec::new());
+        let mut values = Vec::with_capacity(args.len());
+        let mut terms = Vec::with_capacity(args.len());
+        for arg in args {
+            let expr = match arg {
+                ast::FunctionArg::Unnamed(ast::FunctionArgExpr::Expr(expr)) => expr,
+                arg => return Err(format!("Unsupported table function argument: {}", arg).into()),
+            };
+            // arguments are constant, so they are evaluated while planning
+            let expr = Expression::parse(expr, &schema)?;
+            terms.push(expr.display(&schema).to_string());
+            values.push(expr.eval(&row)?);
+        }
+
+        let (schema, batches) = function.call(&values)?;
+        let call = format!("{}({})", name, terms.join(", "));
+        log::debug!(target: "camellia::plan", "table function {}", call);
+        Ok(Box::new(FunctionScan::new(call, schema, batches)))
+    }
+
+    fn check_access(&self, table: &str, access: Access) -> Result<()> {
+        for hook in &self.access_hooks {
+            hook.check(table, access)?;
+        }
+        Ok(())
+    }
+
+    // Whether all rows of |table| may be read, so that its statistics may be reported
+    fn is_visible(&self, table: &str) -> bool {
+        self.check_access(table, Access::Read).is_ok()
+            && matches!(self.access_predicate(table, Access::Read), Ok(None))
+    }
+
+    // Predicates of all access hooks for |table| combined with AND
+    fn access_predicate(&self, table: &str, access: Access) -> Result<Option<ast::Expr>> {
+        let mut result: Option<ast::Expr> = None;
+        for hook in &self.access_hooks {
+            let Some(predicate) = hook.predicate(table, access) else {
+                continue;
+            };
+
+            // fixed dialect, predicates must not change meaning with the dialect of clients
+            let mut parser =
+                Parser::new(Dialect::Generic.parser_dialect()).try_with_sql(&predicate)?;
+            let expr = parser.parse_expr()?;
+            // trailing tokens would be silently dropped otherwise, e.g. "a = 1 garbage"
+            if parser.peek_token().token != Token::EOF {
+                return Err(
+                    format!("Invalid access predicate of table {}: {}", table, predicate).into(),
+                );
+            }
+            let predicate = ast::Expr::Nested(Box::new(expr));
+            result = Some(match result {
+                Some(left) => ast::Expr::BinaryOp {
+                    left: Box::new(left),
+                    op: ast::BinaryOperator::And,
+                    right: Box::new(predicate),
+                },
+                None => predicate,
+            });
+        }
+
+        Ok(result)
+    }
+
     #[trace]
     fn get_table(
         &self,
         table: String,
-        cf: &ColumnFamily<'_>,
-        transaction: &Transaction<'_, Database>,
+        transaction: &dyn Transaction,
     ) -> Result<Arc<Table>> {
-        if let Some(table) = self.tables.read().unwrap().get(&table).cloned() {
-            return Ok(table);
+        // catalog may be changed by another engine, validate cached table within |transaction|
+        let generation = self.read_generation(&table, transaction)?;
+        if let Some(cached) = self.tables.read().unwrap().get(&table).cloned() {
+            if cached.generation() == generation {
+                return Ok(cached);
+            }
+
+            log::debug!(
+                target: "camellia::txn",
+                "table {} changed from generation {} to {}, reloading",
+                table,
+                cached.generation(),
+                generation
+            );
         }
 
-        let schema = self.read_schema(&table, transaction)?;
+        let CatalogEntry { generation, schema } = self.read_catalog_entry(&table, transaction)?;
         let hidden_pk = if schema.primary_key.is_none() {
-            self.read_hidden_pk(cf, transaction)?
+            self.read_hidden_pk(&table, transaction)?
         } else {
             0
         };
 
-        let t = Arc::new(Table::new(schema, hidden_pk));
-        self.tables
-            .write()
-            .unwrap()
-            .entry(table)
-            .or_insert(t.clone());
-        Ok(t)
+        let rows = self.read_row_count(&table, transaction)?;
+        let t = Arc::new(Table::new(schema, generation, hidden_pk, rows));
+        let mut tables = self.tables.write().unwrap();
+        match tables.get(&table) {
+            // loaded concurrently, keep single instance to not reuse hidden keys
+            Some(cached) if cached.generation() == generation => Ok(cached.clone()),
+            _ => {
+                tables.insert(table, t.clone());
+                Ok(t)
+            }
+        }
+    }
+
+    #[trace]
+    fn read_row_count(&self, table: &str, transaction: &dyn Transaction) -> Result<u64> {
+        if self.storage.has_cf(ROW_COUNTS) {
+            if let Some(bytes) = transaction.get(ROW_COUNTS, table.as_bytes())? {
+                let bytes = bytes.try_into().map_err(|_| "Invalid row count")?;
+                return Ok(u64::from_be_bytes(bytes));
+            }
+        }
+
+        // never persisted, e.g. table is created before row counts were introduced.
+        // Storage estimate is cheap, unlike counting rows of the whole table
+        let rows = self
+            .storage
+            .properties(table)?
+            .into_iter()
+            .find(|(name, _)| *name == "estimated_keys")
+            .map_or(0, |(_, rows)| rows);
+        Ok(rows)
+    }
+
+    #[trace]
+    fn persist_row_count(&self, name: &str, table: &Table) -> Result<()> {
+        // cf may be created concurrently by another thread
+        if !self.storage.has_cf(ROW_COUNTS) {
+            if let Err(e) = self.storage.create_cf(ROW_COUNTS) {
+                if !self.storage.has_cf(ROW_COUNTS) {
+                    return Err(e);
+                }
+            }
+        }
+
+        let rows = table.mark_persisted();
+        let transaction = self.storage.transaction();
+        transaction.put(ROW_COUNTS, name.as_bytes(), &rows.to_be_bytes())?;
+        transaction.commit()?;
+        Ok(())
     }
 
     #[trace]
-    fn read_schema(&self, table: &str, transaction: &Transaction<'_, Database>) -> Result<Schema> {
+    fn read_catalog_entry(
+        &self,
+        table: &str,
+        transaction: &dyn Transaction,
+    ) -> Result<CatalogEntry> {
         let bytes = transaction
-            .get(table)?
+            .get(CATALOG, table.as_bytes())?
             .ok_or("Schema for this table not found")?;
-        let schema = bincode::deserialize(&bytes)?;
-        Ok(schema)
+        match bytes.split_first() {
+            Some((&CATALOG_FORMAT, entry)) => Ok(bincode::deserialize(entry)?),
+            // written before catalog entries had generations
+            Some((0 | 1, _)) => Ok(CatalogEntry {
+                generation: 0,
+                schema: bincode::deserialize::<LegacySchema>(&bytes)?.into(),
+            }),
+            _ => Err(format!("Unsupported catalog entry format of table {}", table).into()),
+        }
+    }
+
+    // Same as read_catalog_entry, but decodes only generation
+    fn read_generation(&self, table: &str, transaction: &dyn Transaction) -> Result<u64> {
+        let bytes = transaction
+            .get(CATALOG, table.as_bytes())?
+            .ok_or("Schema for this table not found")?;
+        match bytes.split_first() {
+            // bincode ignores trailing bytes
+            Some((&CATALOG_FORMAT, entry)) => Ok(bincode::deserialize(entry)?),
+            Some((0 | 1, _)) => Ok(0),
+            _ => Err(format!("Unsupported catalog entry format of table {}", table).into()),
+        }
     }
 
     #[trace]
     fn read_hidden_pk(
         &self,
-        cf: &ColumnFamily<'_>,
-        transaction: &Transaction<'_, Database>,
+        table: &str,
+        transaction: &dyn Transaction,
     ) -> Result<u64> {
-        let mut iter = transaction.iterator_cf(cf, IteratorMode::End);
+        let mut iter = transaction.iter(table, Direction::Reverse)?;
         match iter.next().transpose()? {
+            // next key after the last used one
             Some((key, _value)) => {
-                assert!(key.len() == 8);
-                Ok(u64::from_be_bytes([
-                    key[0], key[1], key[2], key[3], key[4], key[5], key[6], key[7],
-                ]))
+                let key = <[u8; 8]>::try_from(&*key).map_err(|_| "Invalid hidden key")?;
+                Ok(u64::from_be_bytes(key) + 1)
             }
             None => Ok(0),
         }
     }
 }
 
+impl Drop for Engine {
+    // Row counts are persisted only periodically, save the rest
+    fn drop(&mut self) {
+        let tables = std::mem::take(&mut *self.tables.write().unwrap());
+        for (name, table) in tables {
+            if !table.has_unpersisted_rows() {
+                continue;
+            }
+
+            if let Err(e) = self.persist_row_count(&name, &table) {
+                log::warn!(
+                    target: "camellia::storage",
+                    "failed to persist row count of {}: {}",
+                    name,
+                    e
+                );
+            }
+        }
+    }
+}
+
+// Table name with unquoted identifier folded to lower case
+fn table_name(name: &ast::ObjectName) -> Result<String> {
+    match name.0.as_slice() {
+        [ident] => Ok(normalize_ident(ident)),
+        _ => Err(format!("Unsupported table name: {}", name).into()),
+    }
+}
+
+// sqlparser reports location only as a part of message, e.g.
+// "Expected end of statement, found: 2 at Line: 1, Column 10"
+fn parse_error(program: &str, e: ParserError, next: TokenWithLocation) -> Error {
+    let message = match e {
+        ParserError::TokenizerError(message) | ParserError::ParserError(message) => message,
+        e => e.to_string(),
+    };
+
+    let (message, location) = match message.split_once(" at Line: ") {
+        Some((message, location)) => (message.to_owned(), parse_location(location)),
+        None => (message, None),
+    };
+    let token = match message.split_once(", found: ") {
+        Some((_, "EOF")) => None,
+        Some((_, found)) => Some(found.to_owned()),
+        None if next.token == Token::EOF => None,
+        None => Some(next.token.to_string()),
+    };
+
+    // error wasn't at specific token, point right after the last character
+    let (line, column) = match location {
+        Some(location) => location,
+        None if next.token != Token::EOF => (next.location.line, next.location.column),
+        None => {
+            let end = end_location(program);
+            (end.line, end.column)
+        }
+    };
+
+    Error::Parse {
+        message,
+        line,
+        column,
+        token,
+    }
+}
+
+// "1, Column 10" => (1, 10)
+fn parse_location(location: &str) -> Option<(u64, u64)> {
+    let (line, column) = location.split_once(", Column ")?;
+    Some((line.parse().ok()?, column.trim().parse().ok()?))
+}
+
+// Location right after the last character of |program|
+fn end_location(program: &str) -> Location {
+    let last = program.lines().last().unwrap_or_default();
+    Location {
+        line: program.lines().count().max(1) as u64,
+        column: last.chars().count() as u64 + 1,
+    }
+}
+
+// Hex string token written as X'..' rather than 0x.., see Engine::parse. Both are tokenized
+// the same, but quotes make X'..' at least 3 characters longer than its digits, 0x only 2.
+// |next| is location of the following token or of the end of program
+fn is_blob_literal(token: &TokenWithLocation, next: Location) -> bool {
+    let Token::HexStringLiteral(digits) = &token.token else {
+        return false;
+    };
+
+    // only quoted string may span lines
+    if next.line != token.location.line {
+        return true;
+    }
+    let width = next.column.saturating_sub(token.location.column);
+    width != digits.chars().count() as u64 + 2
+}
+
+fn check_not_system(table: &str) -> Result<()> {
+    if table.starts_with(SYSTEM_PREFIX) {
+        return Err(format!("Table {} is reserved for internal use", table).into());
+    }
+
+    Ok(())
+}
+
+pub(crate) fn bind(program: &mut Vec<ast::Statement>, params: &[Value]) -> Result<()> {
+    let mut next = 0;
+    let flow = ast::visit_expressions_mut(program, |expr| {
+        let placeholder = match expr {
+            ast::Expr::Value(ast::Value::Placeholder(placeholder)) => placeholder.clone(),
+            _ => return ControlFlow::Continue(()),
+        };
+
+        let index = if placeholder == "?" {
+            next += 1;
+            next - 1
+        } else {
+            match placeholder.strip_prefix('$').map(str::parse::<usize>) {
+                Some(Ok(n)) if n > 0 => n - 1,
+                _ => return ControlFlow::Break(format!("Invalid placeholder: {}", placeholder)),
+            }
+        };
+
+        match params.get(index) {
+            Some(value) => {
+                *expr = ast::Expr::Value(value.to_ast());
+                ControlFlow::Continue(())
+            }
+            None => ControlFlow::Break(format!("No value for parameter {}", placeholder)),
+        }
+    });
+
+    match flow {
+        ControlFlow::Continue(()) => Ok(()),
+        ControlFlow::Break(e) => Err(e.into()),
+    }
+}
+
+// Constant row count of LIMIT, OFFSET or FETCH, negative is no limit (same as in sqlite)
+fn row_count(expr: ast::Expr, clause: &str) -> Result<Option<usize>> {
+    let e = Expression::parse(expr, &Schema::empty())?;
+    match e.eval(&Row::from(Vec::new()))? {
+        Value::Int(n) => Ok(usize::try_from(n).ok()),
+        val => Err(format!("{} must be an integer, got {}", clause, val).into()),
+    }
+}
+
+fn is_aggregate(function: &ast::Function) -> bool {
+    let name = function.name.to_string().to_ascii_lowercase();
+    AggregateFunction::from_name(&name).is_some()
+}
+
+fn has_aggregates<V: ast::Visit>(node: &V) -> bool {
+    let flow = ast::visit_expressions(node, |expr| match expr {
+        ast::Expr::Function(function) if is_aggregate(function) => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
+    });
+    flow.is_break()
+}
+
+// Replaces aggregate calls and GROUP BY expressions with references to columns of
+// Aggregate output, which are named after them
+struct AggregateRewriter<'a> {
+    group_by: &'a [ast::Expr],
+    group_names: &'a [String],
+    calls: Vec<ast::Function>,
+}
+
+impl ast::VisitorMut for AggregateRewriter<'_> {
+    type Break = ();
+
+    fn pre_visit_expr(&mut self, expr: &mut ast::Expr) -> ControlFlow<()> {
+        let column = match self.group_by.iter().position(|e| *e == *expr) {
+            Some(i) => self.group_names[i].clone(),
+            None => match expr {
+                ast::Expr::Function(function) if is_aggregate(function) => {
+                    if !self.calls.contains(function) {
+                        self.calls.push(function.clone());
+                    }
+                    function.to_string()
+                }
+                _ => return ControlFlow::Continue(()),
+            },
+        };
+
+        // quoted, so that the name is used as is, see normalize_ident
+        *expr = ast::Expr::Identifier(ast::Ident::with_quote('"', column));
+        ControlFlow::Continue(())
+    }
+}
+
+// Aggregate over |source| (and HAVING filter), |projection| and |order_by| are rewritten to
+// refer to its output columns
+fn build_aggregate<'txn>(
+    mut group_by: Vec<ast::Expr>,
+    having: Option<ast::Expr>,
+    projection: &mut [ast::SelectItem],
+    order_by: &mut [ast::OrderByExpr],
+    source: Box<dyn Operation + 'txn>,
+) -> Result<Box<dyn Operation + 'txn>> {
+    let schema = source.schema();
+    for expr in group_by.iter_mut() {
+        // GROUP BY allows to specify column by number or alias, same as ORDER BY
+        if let ast::Expr::Value(ast::Value::Number(n, false)) = expr {
+            let index = n
+                .parse::<usize>()
+                .ok()
+                .filter(|n| (1..=projection.len()).contains(n))
+                .ok_or_else(|| format!("GROUP BY term out of range: {}", n))?;
+            *expr = match &projection[index - 1] {
+                ast::SelectItem::UnnamedExpr(e)
+                | ast::SelectItem::ExprWithAlias { expr: e, .. } => e.clone(),
+                _ => return Err(format!("Invalid GROUP BY term: {}", n).into()),
+            };
+        }
+        resolve_aliases(expr, projection, schema);
+        while let ast::Expr::Nested(inner) = expr {
+            *expr = std::mem::replace(&mut **inner, ast::Expr::Value(ast::Value::Null));
+        }
+    }
+
+    let group_names: Vec<String> = group_by
+        .iter()
+        .map(|expr| match expr {
+            ast::Expr::Identifier(ident) => normalize_ident(ident),
+            expr => expr.to_string(),
+        })
+        .collect();
+
+    let mut rewriter = AggregateRewriter {
+        group_by: &group_by,
+        group_names: &group_names,
+        calls: Vec::new(),
+    };
+    let mut having = having;
+    for item in projection.iter_mut() {
+        let _ = ast::VisitMut::visit(item, &mut rewriter);
+    }
+    for item in order_by.iter_mut() {
+        let _ = ast::VisitMut::visit(item, &mut rewriter);
+    }
+    let _ = ast::VisitMut::visit(&mut having, &mut rewriter);
+    let calls = rewriter.calls;
+
+    let mut names = group_names.clone();
+    names.extend(calls.iter().map(|call| call.to_string()));
+
+    let group_by = group_by
+        .into_iter()
+        .map(|expr| Expression::parse(expr, schema))
+        .collect::<Result<_>>()?;
+    let calls = calls
+        .into_iter()
+        .map(|call| AggregateCall::parse(call, schema))
+        .collect::<Result<_>>()?;
+
+    let mut source: Box<dyn Operation + 'txn> =
+        Box::new(Aggregate::new(group_by, calls, names, source)?);
+    if let Some(having) = having {
+        source = Box::new(Filter::new(having, source)?);
+    }
+    Ok(source)
+}
+
+// Replace column references qualified by |relation|, e.g. `t.a`, with plain ones, since
+// there is at most one source of rows. Other qualifiers are unknown tables
+fn resolve_qualified<V: ast::VisitMut>(node: &mut V, relation: Option<&str>) -> Result<()> {
+    let flow = ast::visit_expressions_mut(node, |expr| {
+        if let ast::Expr::CompoundIdentifier(idents) = expr {
+            if let [qualifier, column] = idents.as_slice() {
+                let qualifier = normalize_ident(qualifier);
+                if relation != Some(qualifier.as_str()) {
+                    return ControlFlow::Break(format!("No such table: {}", qualifier));
+                }
+                *expr = ast::Expr::Identifier(column.clone());
+            }
+        }
+        ControlFlow::Continue(())
+    });
+
+    match flow {
+        ControlFlow::Continue(()) => Ok(()),
+        ControlFlow::Break(e) => Err(e.into()),
+    }
+}
+
+// Replace references to projection aliases in |expr| with aliased expressions, so that
+// `SELECT a + b AS s FROM t WHERE s > 10` works like in MySQL and sqlite.
+// Columns of |schema| take precedence over aliases
+fn resolve_aliases(expr: &mut ast::Expr, projection: &[ast::SelectItem], schema: &Schema) {
+    let aliases: HashMap<String, &ast::Expr> = projection
+        .iter()
+        .filter_map(|item| match item {
+            ast::SelectItem::ExprWithAlias { expr, alias } => Some((normalize_ident(alias), expr)),
+            _ => None,
+        })
+        .filter(|(alias, _)| !schema.columns().any(|column| column.name == *alias))
+        .collect();
+    if aliases.is_empty() {
+        return;
+    }
+
+    // NOTE: expressions are visited bottom-up, substituted ones are not visited again
+    let _ = ast::visit_expressions_mut(expr, |expr| {
+        if let ast::Expr::Identifier(ident) = expr {
+            if let Some(aliased) = aliases.get(&normalize_ident(ident)) {
+                *expr = ast::Expr::Nested(Box::new((*aliased).clone()));
+            }
+        }
+        ControlFlow::<()>::Continue(())
+    });
+}
+
+// All columns of |schema| except excluded ones, e.g. `* EXCLUDE (secret)`,
+// with replaced expressions, e.g. `* REPLACE (x + 1 AS x)` (duckdb syntax)
+fn expand_wildcard(
+    options: ast::WildcardAdditionalOptions,
+    schema: &Schema,
+    columns: &mut Vec<Column>,
+    expressions: &mut Vec<Expression>,
+) -> Result<()> {
+    let (exclude, replacements) = match options {
+        ast::WildcardAdditionalOptions {
+            opt_except: None,
+            opt_exclude,
+            opt_rename: None,
+            opt_replace,
+        } => (opt_exclude, opt_replace),
+        _ => return Err("Unsupported wildcard options".into()),
+    };
+
+    let exclude = match exclude {
+        None => Vec::new(),
+        Some(ast::ExcludeSelectItem::Single(ident)) => vec![ident],
+        Some(ast::ExcludeSelectItem::Multiple(idents)) => idents,
+    };
+    let exclude: Vec<_> = exclude.iter().map(normalize_ident).collect();
+
+    let mut replace: HashMap<String, ast::Expr> = HashMap::new();
+    for element in replacements.into_iter().flat_map(|r| r.items) {
+        let name = normalize_ident(&element.column_name);
+        if replace.insert(name.clone(), element.expr).is_some() {
+            return Err(format!("Column {} is replaced more than once", name).into());
+        }
+    }
+
+    for name in exclude.iter().chain(replace.keys()) {
+        if !schema.columns().any(|column| column.name == *name) {
+            return Err(format!("No such column: {}", name).into());
+        }
+    }
+
+    for (i, column) in schema.columns().enumerate() {
+        if exclude.contains(&column.name) {
+            continue;
+        }
+
+        match replace.remove(&column.name) {
+            Some(expr) => {
+                let e = Expression::parse(expr, schema)?;
+                columns.push(Column {
+                    name: column.name.clone(),
+                    type_: e.result_type(schema)?,
+                });
+                expressions.push(e);
+            }
+            None => {
+                columns.push(column.clone());
+                expressions.push(Expression::Field(i));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// NOTE: |relation| is the name of the only source of rows, if any
 fn expand_select(
     exprs: Vec<ast::SelectItem>,
+    relation: Option<&str>,
     schema: &Schema,
 ) -> Result<(Schema, Vec<Expression>)> {
     let mut columns = Vec::with_capacity(exprs.len());
     let mut expressions = Vec::with_capacity(exprs.len());
     for item in exprs {
         match item {
-            ast::SelectItem::Wildcard(ast::WildcardAdditionalOptions {
-                opt_except: None,
-                opt_exclude: None,
-                opt_rename: None,
-                opt_replace: None,
-            }) => {
-                for (i, column) in schema.columns().enumerate() {
-                    columns.push(column.clone());
-                    expressions.push(Expression::Field(i));
+            ast::SelectItem::Wildcard(options) => {
+                expand_wildcard(options, schema, &mut columns, &mut expressions)?;
+            }
+            // t.*
+            ast::SelectItem::QualifiedWildcard(name, options) => {
+                let name = table_name(&name)?;
+                if relation != Some(name.as_str()) {
+                    return Err(format!("No such table: {}", name).into());
                 }
+
+                expand_wildcard(options, schema, &mut columns, &mut expressions)?;
             }
             ast::SelectItem::UnnamedExpr(expr) => {
                 let e = Expression::parse(expr, schema)?;
@@ -506,17 +1704,17 @@ fn expand_select(
             ast::SelectItem::ExprWithAlias { expr, alias } => {
                 let e = Expression::parse(expr, schema)?;
                 columns.push(Column {
-                    name: alias.to_string(),
+                    name: normalize_ident(&alias),
                     type_: e.result_type(schema)?,
                 });
                 expressions.push(e);
             }
-            _ => return Err("Unsupported projection type".into()),
         }
     }
 
     let schema = Schema {
         primary_key: None,
+        strict: false,
         columns,
     };
 
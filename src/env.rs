@@ -0,0 +1,120 @@
+// Sources of non-determinism used while executing statements: random() and wall clock.
+//
+// Simulated environment makes runs reproducible for property-based and fuzz tests:
+//
+//  let engine = Engine::builder().simulated(42);
+//  engine.run_sql("select random()")?;        // same value for the same seed
+//  engine.env().advance(Duration::from_secs(1));
+//
+// NOTE: statements are executed on the calling thread, the engine doesn't spawn threads,
+// so the order of execution is fully determined by the caller
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+pub struct Env {
+    kind: Kind,
+}
+
+enum Kind {
+    System {
+        keys: RandomState,
+        counter: AtomicU64,
+    },
+    Simulated {
+        // splitmix64 state
+        state: AtomicU64,
+        now: Mutex<SystemTime>,
+    },
+}
+
+impl Env {
+    pub fn system() -> Self {
+        Env {
+            kind: Kind::System {
+                keys: RandomState::new(),
+                counter: AtomicU64::new(0),
+            },
+        }
+    }
+
+    // Random numbers are derived from |seed|, clock is frozen at unix epoch until advanced
+    pub fn simulated(seed: u64) -> Self {
+        Env {
+            kind: Kind::Simulated {
+                state: AtomicU64::new(seed),
+                now: Mutex::new(SystemTime::UNIX_EPOCH),
+            },
+        }
+    }
+
+    pub fn is_simulated(&self) -> bool {
+        matches!(self.kind, Kind::Simulated { .. })
+    }
+
+    pub fn random(&self) -> i64 {
+        match &self.kind {
+            Kind::System { keys, counter } => {
+                let mut hasher = keys.build_hasher();
+                hasher.write_u64(counter.fetch_add(1, Ordering::Relaxed));
+                hasher.finish() as i64
+            }
+            Kind::Simulated { state, .. } => {
+                let mut z = state
+                    .fetch_add(0x9e3779b97f4a7c15, Ordering::Relaxed)
+                    .wrapping_add(0x9e3779b97f4a7c15);
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+                (z ^ (z >> 31)) as i64
+            }
+        }
+    }
+
+    // NOTE: system clock is not available on wasm32-unknown-unknown
+    pub fn now(&self) -> SystemTime {
+        match &self.kind {
+            Kind::System { .. } => SystemTime::now(),
+            Kind::Simulated { now, .. } => *now.lock().unwrap(),
+        }
+    }
+
+    // Move simulated clock forward, no-op for system environment
+    pub fn advance(&self, by: Duration) {
+        if let Kind::Simulated { now, .. } = &self.kind {
+            *now.lock().unwrap() += by;
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<Env>>> = RefCell::new(None);
+}
+
+// Make |env| current for expressions evaluated on this thread until the guard is dropped
+pub(crate) fn enter(env: Arc<Env>) -> EnvGuard {
+    let previous = CURRENT.with(|current| current.replace(Some(env)));
+    EnvGuard { previous }
+}
+
+pub(crate) struct EnvGuard {
+    previous: Option<Arc<Env>>,
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+// random() of the current environment, see enter
+pub(crate) fn random() -> i64 {
+    CURRENT.with(|current| match &*current.borrow() {
+        Some(env) => env.random(),
+        // evaluated outside of Engine::run
+        None => Env::system().random(),
+    })
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+
+// Errors which callers may want to tell apart, e.g.
+//
+//  match engine.run_sql("select 1 / 0") {
+//      Err(e) if e.downcast_ref() == Some(&camellia::Error::DivisionByZero) => ...
+//  }
+//
+// NOTE: everything else is still reported as plain string error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    DivisionByZero,
+    // integer overflow in operation, e.g. "ADD"
+    Overflow(&'static str),
+    // numeric literal which doesn't fit into INT, e.g. "0x10000000000000000"
+    OutOfRange(String),
+    // statement can't be parsed, |line| and |column| are 1-based position of |token|,
+    // which is None at the end of input
+    Parse {
+        message: String,
+        line: u64,
+        column: u64,
+        token: Option<String>,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DivisionByZero => write!(f, "Division by zero"),
+            Error::Overflow(op) => write!(f, "Integer overflow on {}", op),
+            Error::OutOfRange(literal) => write!(f, "Integer literal out of range: {}", literal),
+            Error::Parse {
+                message,
+                line,
+                column,
+                ..
+            } => write!(f, "{} at line {}, column {}", message, line, column),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -2,8 +2,9 @@ use core::fmt;
 
 use sqlparser::ast::{self, Function};
 
-use crate::schema::{Schema, Type};
-use crate::types::{Result, Row, Value};
+use crate::env;
+use crate::schema::{normalize_ident, Schema, Type};
+use crate::types::{parse_int, Result, Row, Value};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Op {
@@ -11,6 +12,7 @@ pub enum Op {
     Sub,
     Mul,
     Div,
+    Mod,
 
     And,
     Or,
@@ -30,6 +32,7 @@ impl fmt::Display for Op {
             Op::Sub => "-",
             Op::Mul => "*",
             Op::Div => "/",
+            Op::Mod => "%",
             Op::And => "AND",
             Op::Or => "OR",
             Op::Equal => "=",
@@ -69,12 +72,43 @@ pub enum Expression {
     Const(Value),
 
     Abs(Box<Expression>),
+    Random,
     UnaryOp(UnaryOp, Box<Expression>),
     BinOp(Box<Expression>, Op, Box<Expression>),
     Case(Vec<(Expression, Expression)>, Option<Box<Expression>>),
+    // [NOT] IN (...), NULL unless matched or all of list and the value are not NULL
+    InList {
+        expr: Box<Expression>,
+        list: Vec<Expression>,
+        negated: bool,
+    },
+    // [NOT] BETWEEN low AND high, same as expr >= low AND expr <= high
+    Between {
+        expr: Box<Expression>,
+        low: Box<Expression>,
+        high: Box<Expression>,
+        negated: bool,
+    },
+    // [NOT] LIKE with % and _ wildcards, case-insensitive for ASCII like in sqlite
+    Like {
+        expr: Box<Expression>,
+        pattern: Box<Expression>,
+        negated: bool,
+    },
+    // IS [NOT] TRUE/FALSE/UNKNOWN, None is UNKNOWN (same as IS NULL), never evaluates to NULL
+    Is {
+        expr: Box<Expression>,
+        value: Option<bool>,
+        negated: bool,
+    },
 }
 
 impl Expression {
+    // SQL-like representation, fields are named after columns of |schema|
+    pub fn display<'a>(&'a self, schema: &'a Schema) -> Display<'a> {
+        Display { expr: self, schema }
+    }
+
     pub fn eval(&self, row: &Row) -> Result<Value> {
         match self {
             Expression::Field(index) => Ok(row.get(*index).clone()),
@@ -82,20 +116,12 @@ impl Expression {
             Expression::UnaryOp(op, expr) => {
                 let val = expr.eval(row)?;
                 match op {
-                    UnaryOp::Not => {
-                        let val = val
-                            .to_bool()
-                            .ok_or("Cannot convert to BOOL for NOT operation")?;
-                        Ok(Value::Bool(!val))
-                    }
+                    UnaryOp::Not => val.not(),
                     UnaryOp::Plus => {
                         // does not do anything
                         Ok(val)
                     }
-                    UnaryOp::Minus => {
-                        let val = val.to_int().ok_or("Cannot convert to INT for unary '-'")?;
-                        Ok(Value::Int(-val))
-                    }
+                    UnaryOp::Minus => val.neg(),
                 }
             }
             Expression::BinOp(left, op, right) => {
@@ -106,10 +132,13 @@ impl Expression {
                     Op::Sub => left.sub(right),
                     Op::Mul => left.mul(right),
                     Op::Div => left.div(right),
+                    Op::Mod => left.rem(right),
 
                     Op::And => left.and(right),
                     Op::Or => left.or(right),
 
+                    // comparison with NULL is unknown
+                    _ if left.is_null() || right.is_null() => Ok(Value::Null),
                     Op::Equal => Ok(Value::Bool(left == right)),
                     Op::NotEqual => Ok(Value::Bool(left != right)),
                     Op::Less => Ok(Value::Bool(left < right)),
@@ -120,7 +149,15 @@ impl Expression {
             }
             Expression::Case(cases, otherwise) => {
                 for (condition, result) in cases {
-                    if condition.eval(row)?.to_bool().unwrap() {
+                    // NULL condition is not satisfied, same as false
+                    let satisfied = match condition.eval(row)? {
+                        Value::Null => false,
+                        val => val
+                            .to_bool()
+                            .ok_or("Cannot convert condition to BOOL (CASE-WHEN)")?,
+                    };
+
+                    if satisfied {
                         return result.eval(row);
                     }
                 }
@@ -131,13 +168,86 @@ impl Expression {
                     Ok(Value::Null)
                 }
             }
-            Expression::Abs(arg) => {
-                let val = arg
-                    .eval(row)?
-                    .to_int()
-                    .ok_or("Cannot convert 'abs' arg to integer")?;
+            Expression::Abs(arg) => arg.eval(row)?.abs(),
+            Expression::Random => Ok(Value::Int(env::random())),
+            Expression::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                let val = expr.eval(row)?;
+                if val.is_null() {
+                    return Ok(Value::Null);
+                }
+
+                let mut result = Value::Bool(false);
+                for item in list {
+                    let item = item.eval(row)?;
+                    if item.is_null() {
+                        result = Value::Null;
+                    } else if item == val {
+                        result = Value::Bool(true);
+                        break;
+                    }
+                }
 
-                Ok(Value::Int(val.abs()))
+                if *negated {
+                    result.not()
+                } else {
+                    Ok(result)
+                }
+            }
+            Expression::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                let val = expr.eval(row)?;
+                let low = low.eval(row)?;
+                let high = high.eval(row)?;
+                // comparison with NULL is unknown
+                let compare = |ok: bool, bound: &Value| {
+                    if val.is_null() || bound.is_null() {
+                        Value::Null
+                    } else {
+                        Value::Bool(ok)
+                    }
+                };
+
+                let result = compare(val >= low, &low).and(compare(val <= high, &high))?;
+                if *negated {
+                    result.not()
+                } else {
+                    Ok(result)
+                }
+            }
+            Expression::Like {
+                expr,
+                pattern,
+                negated,
+            } => {
+                let result = match (expr.eval(row)?, pattern.eval(row)?) {
+                    (Value::Null, _) | (_, Value::Null) => return Ok(Value::Null),
+                    (Value::String(s), Value::String(p)) => like(&s, &p),
+                    _ => return Err("LIKE can only be applied to TEXT".into()),
+                };
+                Ok(Value::Bool(result != *negated))
+            }
+            Expression::Is {
+                expr,
+                value,
+                negated,
+            } => {
+                let val = expr.eval(row)?;
+                let is = match value {
+                    None => val.is_null(),
+                    Some(value) => {
+                        let truth = val.to_truth().ok_or("Cannot convert to BOOL for IS")?;
+                        truth == Some(*value)
+                    }
+                };
+                Ok(Value::Bool(is != *negated))
             }
         }
     }
@@ -182,7 +292,7 @@ impl Expression {
                 let left = left.result_type(schema)?;
                 let right = right.result_type(schema)?;
                 match *op {
-                    Op::Add | Op::Sub | Op::Mul | Op::Div => {
+                    Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Mod => {
                         if !left.convertable_to(Type::Integer)
                             || !right.convertable_to(Type::Integer)
                         {
@@ -210,11 +320,11 @@ impl Expression {
                     | Op::GreaterOrEqual
                     | Op::Less
                     | Op::LessOrEqual => {
-                        if left != right {
+                        if left != right && left != Type::Null && right != Type::Null {
                             return Err(format!("Attempt to compare values of different types ({left} and {right}) with {op}").into());
                         }
 
-                        Ok(left)
+                        Ok(Type::Bool)
                     }
                 }
             }
@@ -230,8 +340,62 @@ impl Expression {
 
                 Ok(Type::Integer)
             }
+            Expression::Random => Ok(Type::Integer),
+            Expression::InList { expr, list, .. } => {
+                let t = expr.result_type(schema)?;
+                for item in list {
+                    let item_type = item.result_type(schema)?;
+                    if t != item_type && t != Type::Null && item_type != Type::Null {
+                        return Err(format!(
+                            "Attempt to compare values of different types ({t} and {item_type}) with IN"
+                        )
+                        .into());
+                    }
+                }
+
+                Ok(Type::Bool)
+            }
+            Expression::Between {
+                expr, low, high, ..
+            } => {
+                let t = expr.result_type(schema)?;
+                for bound in [low, high] {
+                    let bound_type = bound.result_type(schema)?;
+                    if t != bound_type && t != Type::Null && bound_type != Type::Null {
+                        return Err(format!(
+                            "Attempt to compare values of different types ({t} and {bound_type}) with BETWEEN"
+                        )
+                        .into());
+                    }
+                }
+
+                Ok(Type::Bool)
+            }
+            Expression::Like { expr, pattern, .. } => {
+                for e in [expr, pattern] {
+                    let t = e.result_type(schema)?;
+                    if t != Type::Text && t != Type::Null {
+                        return Err(format!(
+                            "Invalid LIKE: cannot be applied to expression of type {t}"
+                        )
+                        .into());
+                    }
+                }
+
+                Ok(Type::Bool)
+            }
+            Expression::Is { expr, value, .. } => {
+                let t = expr.result_type(schema)?;
+                if value.is_some() && !t.convertable_to(Type::Bool) {
+                    return Err(
+                        format!("Invalid IS: cannot be applied to expression of type {t}").into(),
+                    );
+                }
+
+                Ok(Type::Bool)
+            }
             Expression::Case(cases, otherwise) => {
-                let (_, result) = cases.first().expect("Empty case-when");
+                let (_, result) = cases.first().ok_or("Empty CASE-WHEN")?;
                 let result_type = result.result_type(schema)?;
                 for (c, r) in cases {
                     let c_type = c.result_type(schema)?;
@@ -284,21 +448,30 @@ impl Expression {
                 order_by,
             }) if order_by.is_empty() => {
                 let name = name.to_string().to_ascii_lowercase();
-                if name != "abs" {
-                    return Err(format!("Unknown function: {}", name).into());
-                }
+                let arity = match name.as_str() {
+                    "abs" => 1,
+                    "random" => 0,
+                    _ => return Err(format!("Unknown function: {}", name).into()),
+                };
 
-                if args.len() != 1 {
+                if args.len() != arity {
                     return Err(format!("Invalid number of arguments for {} function", name).into());
                 }
 
-                let arg = match args.into_iter().next().unwrap() {
-                    ast::FunctionArg::Unnamed(ast::FunctionArgExpr::Expr(e)) => e,
-                    _ => return Err("Unsupported function arg kind".into()),
-                };
+                let mut parsed = Vec::with_capacity(args.len());
+                for arg in args {
+                    let arg = match arg {
+                        ast::FunctionArg::Unnamed(ast::FunctionArgExpr::Expr(e)) => e,
+                        _ => return Err("Unsupported function arg kind".into()),
+                    };
+                    parsed.push(Expression::parse(arg, schema)?);
+                }
 
-                let e = Expression::parse(arg, schema)?;
-                Ok(Expression::Abs(Box::new(e)))
+                let mut args = parsed.into_iter();
+                match name.as_str() {
+                    "abs" => Ok(Expression::Abs(Box::new(args.next().unwrap()))),
+                    _ => Ok(Expression::Random),
+                }
             }
             ast::Expr::Case {
                 operand: None,
@@ -306,7 +479,10 @@ impl Expression {
                 results,
                 else_result,
             } => {
-                assert!(conditions.len() == results.len());
+                if conditions.is_empty() || conditions.len() != results.len() {
+                    return Err("Invalid CASE-WHEN".into());
+                }
+
                 let mut cases = Vec::with_capacity(conditions.len());
 
                 for (condition, result) in conditions.into_iter().zip(results) {
@@ -322,6 +498,16 @@ impl Expression {
                 Ok(Expression::Case(cases, otherwise))
             }
             ast::Expr::UnaryOp { op, expr } => {
+                // -9223372036854775808 is INT, while 9223372036854775808 alone is out of range
+                if let (
+                    ast::UnaryOperator::Minus,
+                    ast::Expr::Value(ast::Value::Number(number, _)),
+                ) = (&op, &*expr)
+                {
+                    let val = parse_int(&format!("-{}", number))?;
+                    return Ok(Expression::Const(Value::Int(val)));
+                }
+
                 let e = Expression::parse(*expr, schema)?;
                 let op = match op {
                     ast::UnaryOperator::Not => UnaryOp::Not,
@@ -336,7 +522,7 @@ impl Expression {
                         Expression::Const(Value::Bool(!v))
                     }
                     (UnaryOp::Plus, Expression::Const(v)) => Expression::Const(v),
-                    (UnaryOp::Minus, Expression::Const(Value::Int(v))) => {
+                    (UnaryOp::Minus, Expression::Const(Value::Int(v))) if v != i64::MIN => {
                         Expression::Const(Value::Int(-v))
                     }
                     (op, e) => Expression::UnaryOp(op, Box::new(e)),
@@ -352,6 +538,7 @@ impl Expression {
                     ast::BinaryOperator::Minus => Op::Sub,
                     ast::BinaryOperator::Multiply => Op::Mul,
                     ast::BinaryOperator::Divide => Op::Div,
+                    ast::BinaryOperator::Modulo => Op::Mod,
 
                     ast::BinaryOperator::And => Op::And,
                     ast::BinaryOperator::Or => Op::Or,
@@ -368,15 +555,60 @@ impl Expression {
 
                 Ok(Expression::BinOp(Box::new(left), op, Box::new(right)))
             }
+            ast::Expr::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                let expr = Box::new(Expression::parse(*expr, schema)?);
+                let list = list
+                    .into_iter()
+                    .map(|e| Expression::parse(e, schema))
+                    .collect::<Result<_>>()?;
+                Ok(Expression::InList {
+                    expr,
+                    list,
+                    negated,
+                })
+            }
+            ast::Expr::Between {
+                expr,
+                negated,
+                low,
+                high,
+            } => Ok(Expression::Between {
+                expr: Box::new(Expression::parse(*expr, schema)?),
+                low: Box::new(Expression::parse(*low, schema)?),
+                high: Box::new(Expression::parse(*high, schema)?),
+                negated,
+            }),
+            ast::Expr::Like {
+                negated,
+                expr,
+                pattern,
+                escape_char: None,
+            } => Ok(Expression::Like {
+                expr: Box::new(Expression::parse(*expr, schema)?),
+                pattern: Box::new(Expression::parse(*pattern, schema)?),
+                negated,
+            }),
+            ast::Expr::IsTrue(e) => Expression::parse_is(*e, Some(true), false, schema),
+            ast::Expr::IsNotTrue(e) => Expression::parse_is(*e, Some(true), true, schema),
+            ast::Expr::IsFalse(e) => Expression::parse_is(*e, Some(false), false, schema),
+            ast::Expr::IsNotFalse(e) => Expression::parse_is(*e, Some(false), true, schema),
+            ast::Expr::IsUnknown(e) | ast::Expr::IsNull(e) => {
+                Expression::parse_is(*e, None, false, schema)
+            }
+            ast::Expr::IsNotUnknown(e) | ast::Expr::IsNotNull(e) => {
+                Expression::parse_is(*e, None, true, schema)
+            }
             ast::Expr::Nested(e) => Expression::parse(*e, schema),
-            ast::Expr::Identifier(ast::Ident {
-                value,
-                quote_style: None,
-            }) => {
+            ast::Expr::Identifier(ident) => {
+                let name = normalize_ident(&ident);
                 let index = schema
                     .columns()
-                    .position(|column| column.name == value)
-                    .ok_or_else(|| format!("No such column: {}", value))?;
+                    .position(|column| column.name == name)
+                    .ok_or_else(|| format!("No such column: {}", name))?;
                 Ok(Expression::Field(index))
             }
             ast::Expr::Value(val) => {
@@ -386,4 +618,171 @@ impl Expression {
             e => Err(format!("Unsupported expression kind: {:?}", e).into()),
         }
     }
+
+    fn parse_is(
+        expr: ast::Expr,
+        value: Option<bool>,
+        negated: bool,
+        schema: &Schema,
+    ) -> Result<Self> {
+        let expr = Box::new(Expression::parse(expr, schema)?);
+        Ok(Expression::Is {
+            expr,
+            value,
+            negated,
+        })
+    }
+}
+
+// Whether |text| matches LIKE |pattern|: % is any sequence of chars and _ is any single char
+fn like(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+
+    // position of the last % in pattern and text position it matched up to
+    let mut backtrack = None;
+    let (mut t, mut p) = (0, 0);
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('%') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '_' || c.eq_ignore_ascii_case(&text[t]) => {
+                t += 1;
+                p += 1;
+            }
+            _ => match backtrack {
+                // let the last % consume one more char
+                Some((percent, matched)) => {
+                    backtrack = Some((percent, matched + 1));
+                    t = matched + 1;
+                    p = percent + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '%')
+}
+
+pub struct Display<'a> {
+    expr: &'a Expression,
+    schema: &'a Schema,
+}
+
+impl<'a> Display<'a> {
+    fn nested(&self, expr: &'a Expression) -> Self {
+        Display {
+            expr,
+            schema: self.schema,
+        }
+    }
+
+    // Operands which are binary operations themselves are parenthesized
+    fn operand(&self, f: &mut fmt::Formatter<'_>, expr: &'a Expression) -> fmt::Result {
+        match expr {
+            Expression::BinOp(..) => write!(f, "({})", self.nested(expr)),
+            _ => write!(f, "{}", self.nested(expr)),
+        }
+    }
+}
+
+impl<'a> fmt::Display for Display<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.expr {
+            Expression::Field(i) => match self.schema.columns.get(*i) {
+                Some(column) => f.write_str(&column.name),
+                None => write!(f, "#{}", i),
+            },
+            Expression::Const(value) => write!(f, "{}", value.to_ast()),
+            Expression::Abs(arg) => write!(f, "abs({})", self.nested(arg)),
+            Expression::Random => f.write_str("random()"),
+            Expression::UnaryOp(UnaryOp::Not, expr) => {
+                f.write_str("NOT ")?;
+                self.operand(f, expr)
+            }
+            Expression::UnaryOp(op, expr) => {
+                write!(f, "{}", op)?;
+                self.operand(f, expr)
+            }
+            Expression::BinOp(left, op, right) => {
+                self.operand(f, left)?;
+                write!(f, " {} ", op)?;
+                self.operand(f, right)
+            }
+            Expression::Case(cases, otherwise) => {
+                f.write_str("CASE")?;
+                for (condition, result) in cases {
+                    write!(
+                        f,
+                        " WHEN {} THEN {}",
+                        self.nested(condition),
+                        self.nested(result)
+                    )?;
+                }
+                if let Some(otherwise) = otherwise {
+                    write!(f, " ELSE {}", self.nested(otherwise))?;
+                }
+                f.write_str(" END")
+            }
+            Expression::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                self.operand(f, expr)?;
+                f.write_str(if *negated { " NOT IN (" } else { " IN (" })?;
+                for (i, item) in list.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", self.nested(item))?;
+                }
+                f.write_str(")")
+            }
+            Expression::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                self.operand(f, expr)?;
+                f.write_str(if *negated {
+                    " NOT BETWEEN "
+                } else {
+                    " BETWEEN "
+                })?;
+                self.operand(f, low)?;
+                f.write_str(" AND ")?;
+                self.operand(f, high)
+            }
+            Expression::Like {
+                expr,
+                pattern,
+                negated,
+            } => {
+                self.operand(f, expr)?;
+                f.write_str(if *negated { " NOT LIKE " } else { " LIKE " })?;
+                self.operand(f, pattern)
+            }
+            Expression::Is {
+                expr,
+                value,
+                negated,
+            } => {
+                self.operand(f, expr)?;
+                f.write_str(" IS ")?;
+                if *negated {
+                    f.write_str("NOT ")?;
+                }
+                f.write_str(match value {
+                    Some(true) => "TRUE",
+                    Some(false) => "FALSE",
+                    None => "NULL",
+                })
+            }
+        }
+    }
 }
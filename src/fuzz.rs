@@ -0,0 +1,41 @@
+// Entry point for fuzzers, see fuzz/fuzz_targets/sql.rs
+use crate::engine::{Engine, Output};
+use crate::schema::Type;
+use crate::types::RowSet;
+
+// Run statements from |data| one by one against fresh simulated engine.
+// Errors are expected, panics and results which disagree with their schema are bugs
+pub fn run(data: &[u8]) {
+    let Ok(sql) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let engine = Engine::builder().simulated(0);
+    let Ok(program) = engine.parse(sql) else {
+        return;
+    };
+
+    for statement in program {
+        if let Ok(Output::Rows(rowset)) = engine.run(vec![statement]) {
+            check(&rowset);
+        }
+    }
+}
+
+fn check(rowset: &RowSet) {
+    let columns = &rowset.schema.columns;
+    for row in &rowset.rows {
+        assert_eq!(row.len(), columns.len(), "Row arity doesn't match schema");
+        for (value, column) in row.values().zip(columns) {
+            let type_ = value.type_();
+            assert!(
+                type_ == Type::Null || type_ == column.type_,
+                "Value {} of type {} in column {} of type {}",
+                value,
+                type_,
+                column.name,
+                column.type_
+            );
+        }
+    }
+}
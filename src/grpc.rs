@@ -0,0 +1,180 @@
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures::Stream;
+use tonic::{Request, Response, Status};
+
+use crate::auth::{parse_basic, Authenticator};
+use crate::engine::{Engine, Output};
+use crate::schema::Type;
+use crate::session::Session;
+use crate::types::{Row, RowSet, Value};
+
+pub mod proto {
+    tonic::include_proto!("camellia");
+}
+
+use proto::camellia_server::{Camellia, CamelliaServer};
+use proto::execute_response::Payload;
+
+// Number of rows sent in single RowBatch message
+const BATCH_SIZE: usize = 1024;
+
+pub struct Service {
+    engine: Arc<Engine>,
+    // None => all requests are executed by anonymous session
+    authenticator: Option<Arc<dyn Authenticator>>,
+}
+
+impl Service {
+    pub fn new(engine: Arc<Engine>) -> Self {
+        Service {
+            engine,
+            authenticator: None,
+        }
+    }
+
+    // Require "authorization: Basic ..." metadata of a user known to |authenticator|
+    pub fn with_authenticator(mut self, authenticator: Arc<dyn Authenticator>) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    pub fn into_server(self) -> CamelliaServer<Self> {
+        CamelliaServer::new(self)
+    }
+}
+
+pub async fn serve(engine: Arc<Engine>, addr: SocketAddr) -> Result<(), tonic::transport::Error> {
+    tonic::transport::Server::builder()
+        .add_service(Service::new(engine).into_server())
+        .serve(addr)
+        .await
+}
+
+type ExecuteStream = Pin<Box<dyn Stream<Item = Result<proto::ExecuteResponse, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl Camellia for Service {
+    type ExecuteStream = ExecuteStream;
+
+    async fn execute(
+        &self,
+        request: Request<proto::ExecuteRequest>,
+    ) -> Result<Response<ExecuteStream>, Status> {
+        let credentials = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_basic);
+        let session = match (&self.authenticator, credentials) {
+            (None, _) => Session::new(self.engine.clone()),
+            (Some(authenticator), Some((user, password))) => {
+                Session::authenticate(self.engine.clone(), &**authenticator, &user, &password)
+                    .map_err(|e| Status::unauthenticated(e.to_string()))?
+            }
+            (Some(_), None) => return Err(Status::unauthenticated("Missing credentials")),
+        };
+        let mut session = session.single_request();
+
+        let proto::ExecuteRequest { sql, params } = request.into_inner();
+        let params: Vec<Value> = params.into_iter().map(Value::from).collect();
+
+        // engine is blocking, don't stall the runtime
+        let output = tokio::task::spawn_blocking(move || session.execute(&sql, &params))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let stream: ExecuteStream = match output {
+            Output::Affected(n) => {
+                let response = proto::ExecuteResponse {
+                    payload: Some(Payload::Affected(n as u64)),
+                };
+                Box::pin(futures::stream::once(async move { Ok::<_, Status>(response) }))
+            }
+            Output::Rows(rowset) => {
+                Box::pin(futures::stream::iter(batches(rowset).map(Ok::<_, Status>)))
+            }
+        };
+
+        Ok(Response::new(stream))
+    }
+}
+
+// Header followed by lazily converted row batches
+fn batches(rowset: RowSet) -> impl Iterator<Item = proto::ExecuteResponse> + Send {
+    let RowSet { schema, rows } = rowset;
+    let columns = schema
+        .columns()
+        .map(|column| proto::Column {
+            name: column.name.clone(),
+            r#type: proto::Type::from(column.type_) as i32,
+        })
+        .collect();
+    let header = proto::ExecuteResponse {
+        payload: Some(Payload::Header(proto::Header { columns })),
+    };
+
+    let mut rows = rows.into_iter();
+    let batches = std::iter::from_fn(move || {
+        let batch: Vec<_> = rows.by_ref().take(BATCH_SIZE).map(proto::Row::from).collect();
+        if batch.is_empty() {
+            return None;
+        }
+
+        Some(proto::ExecuteResponse {
+            payload: Some(Payload::Batch(proto::RowBatch { rows: batch })),
+        })
+    });
+
+    std::iter::once(header).chain(batches)
+}
+
+impl From<Type> for proto::Type {
+    fn from(type_: Type) -> Self {
+        match type_ {
+            Type::Null => proto::Type::Null,
+            Type::Bool => proto::Type::Bool,
+            Type::Integer => proto::Type::Integer,
+            Type::Text => proto::Type::Text,
+        }
+    }
+}
+
+impl From<Row> for proto::Row {
+    fn from(row: Row) -> Self {
+        proto::Row {
+            values: row.values().cloned().map(proto::Value::from).collect(),
+        }
+    }
+}
+
+impl From<Value> for proto::Value {
+    fn from(value: Value) -> Self {
+        use proto::value::Kind;
+
+        let kind = match value {
+            Value::Null => None,
+            Value::Bool(val) => Some(Kind::Bool(val)),
+            Value::Int(val) => Some(Kind::Int(val)),
+            Value::String(val) => Some(Kind::Text(val)),
+        };
+
+        proto::Value { kind }
+    }
+}
+
+impl From<proto::Value> for Value {
+    fn from(value: proto::Value) -> Self {
+        use proto::value::Kind;
+
+        match value.kind {
+            None => Value::Null,
+            Some(Kind::Bool(val)) => Value::Bool(val),
+            Some(Kind::Int(val)) => Value::Int(val),
+            Some(Kind::Text(val)) => Value::String(val),
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::Context;
+
+use camellia::Engine;
+
+const COMMANDS: &[&str] = &[
+    ".maxrows",
+    ".mode",
+    ".nullvalue",
+    ".once",
+    ".output",
+    ".pager",
+    ".read",
+    ".schema",
+    ".tables",
+    ".timing",
+    ":log",
+];
+
+const KEYWORDS: &[&str] = &[
+    "ABS",
+    "AND",
+    "AS",
+    "ASC",
+    "BETWEEN",
+    "BOOL",
+    "BY",
+    "CASE",
+    "COUNT",
+    "CREATE",
+    "DESC",
+    "DISTINCT",
+    "DROP",
+    "ELSE",
+    "END",
+    "EXCLUDE",
+    "EXPLAIN",
+    "FALSE",
+    "FETCH",
+    "FIRST",
+    "FROM",
+    "GROUP",
+    "GROUP_CONCAT",
+    "HAVING",
+    "IN",
+    "INSERT",
+    "INT",
+    "INTO",
+    "IS",
+    "KEY",
+    "LIKE",
+    "LIMIT",
+    "MAX",
+    "MIN",
+    "NOT",
+    "NULL",
+    "OFFSET",
+    "ONLY",
+    "OR",
+    "ORDER",
+    "PRIMARY",
+    "REPLACE",
+    "ROW",
+    "ROWS",
+    "SELECT",
+    "SET",
+    "SHOW",
+    "STATUS",
+    "STRING_AGG",
+    "SUM",
+    "TABLE",
+    "TEXT",
+    "THEN",
+    "TRUE",
+    "UNKNOWN",
+    "VALUES",
+    "WHEN",
+    "WHERE",
+];
+
+const KEYWORD: &str = "\x1b[1;34m";
+const STRING: &str = "\x1b[32m";
+const NUMBER: &str = "\x1b[33m";
+const COMMENT: &str = "\x1b[90m";
+// unterminated quotes and unbalanced parens
+const ERROR: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+// rustyline helper which completes REPL commands, SQL keywords, table and column names
+// and highlights SQL syntax
+pub struct Helper {
+    engine: Arc<Engine>,
+}
+
+impl Helper {
+    pub fn new(engine: Arc<Engine>) -> Self {
+        Helper { engine }
+    }
+
+    // Tables and columns of all tables
+    fn catalog_names(&self) -> Vec<String> {
+        let tables = self.engine.tables().unwrap_or_default();
+        let mut names = Vec::new();
+        for table in tables {
+            if let Ok(schema) = self.engine.schema(&table) {
+                names.extend(schema.columns().map(|column| column.name.clone()));
+            }
+            names.push(table);
+        }
+
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+impl Completer for Helper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos]
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.' || c == ':'))
+            .map_or(0, |i| {
+                i + line[i..].chars().next().map_or(0, char::len_utf8)
+            });
+        let word = &line[start..pos];
+        if word.is_empty() {
+            return Ok((pos, Vec::new()));
+        }
+
+        // REPL commands are only valid at the start of the line
+        if line[..start].trim().is_empty() && (word.starts_with('.') || word.starts_with(':')) {
+            let candidates = COMMANDS
+                .iter()
+                .filter(|command| command.starts_with(word))
+                .map(|command| command.to_string())
+                .collect();
+            return Ok((start, candidates));
+        }
+
+        let lowercase = word.chars().all(|c| !c.is_uppercase());
+        let mut candidates: Vec<String> = KEYWORDS
+            .iter()
+            .filter(|keyword| {
+                keyword.len() >= word.len() && keyword[..word.len()].eq_ignore_ascii_case(word)
+            })
+            .map(|keyword| {
+                // keep case user started typing in
+                if lowercase {
+                    keyword.to_ascii_lowercase()
+                } else {
+                    keyword.to_string()
+                }
+            })
+            .collect();
+
+        candidates.extend(
+            self.catalog_names()
+                .into_iter()
+                .filter(|name| name.starts_with(word)),
+        );
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for Helper {
+    type Hint = String;
+}
+
+impl Highlighter for Helper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        if line.trim_start().starts_with('.') || line.trim_start().starts_with(':') {
+            return Cow::Borrowed(line);
+        }
+
+        Cow::Owned(highlight(line))
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, _forced: bool) -> bool {
+        // any typed char may open or close a quote, re-highlight whole line
+        true
+    }
+}
+
+impl Validator for Helper {}
+
+impl rustyline::Helper for Helper {}
+
+fn highlight(line: &str) -> String {
+    let unmatched = unmatched_parens(line);
+    let mut highlighted = String::with_capacity(line.len() * 2);
+    let mut paint = |text: &str, color: &str| {
+        if color.is_empty() {
+            highlighted.push_str(text);
+        } else {
+            highlighted.push_str(color);
+            highlighted.push_str(text);
+            highlighted.push_str(RESET);
+        }
+    };
+
+    let mut offset = 0;
+    while let Some(c) = line[offset..].chars().next() {
+        let rest = &line[offset..];
+        let len = match c {
+            '\'' | '"' => {
+                let (len, terminated) = quoted_len(rest);
+                match (terminated, c) {
+                    (false, _) => paint(&rest[..len], ERROR),
+                    (true, '\'') => paint(&rest[..len], STRING),
+                    // quoted identifier
+                    (true, _) => paint(&rest[..len], ""),
+                }
+                len
+            }
+            '-' if rest.starts_with("--") => {
+                let len = rest.find('\n').unwrap_or(rest.len());
+                paint(&rest[..len], COMMENT);
+                len
+            }
+            c if c.is_ascii_digit() => {
+                let len = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                paint(&rest[..len], NUMBER);
+                len
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let len = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                let word = &rest[..len];
+                if KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(word)) {
+                    paint(word, KEYWORD);
+                } else {
+                    paint(word, "");
+                }
+                len
+            }
+            '(' | ')' if unmatched.contains(&offset) => {
+                paint(&rest[..1], ERROR);
+                1
+            }
+            c => {
+                paint(&rest[..c.len_utf8()], "");
+                c.len_utf8()
+            }
+        };
+
+        offset += len;
+    }
+
+    highlighted
+}
+
+// Length of quoted string at the start of |s| (including quotes) and whether it is terminated.
+// Quote is escaped by doubling it, i.e. 'it''s'
+fn quoted_len(s: &str) -> (usize, bool) {
+    let quote = match s.chars().next() {
+        Some(c) => c,
+        None => return (0, false),
+    };
+
+    let mut offset = quote.len_utf8();
+    while let Some(i) = s[offset..].find(quote) {
+        let end = offset + i + quote.len_utf8();
+        if s[end..].starts_with(quote) {
+            offset = end + quote.len_utf8();
+            continue;
+        }
+
+        return (end, true);
+    }
+
+    (s.len(), false)
+}
+
+// Byte offsets of parens which have no pair, quoted strings and comments are skipped
+fn unmatched_parens(line: &str) -> Vec<usize> {
+    let mut open = Vec::new();
+    let mut unmatched = Vec::new();
+
+    let mut offset = 0;
+    while let Some(c) = line[offset..].chars().next() {
+        let rest = &line[offset..];
+        let len = match c {
+            '\'' | '"' => quoted_len(rest).0,
+            '-' if rest.starts_with("--") => rest.find('\n').unwrap_or(rest.len()),
+            '(' => {
+                open.push(offset);
+                1
+            }
+            ')' => {
+                if open.pop().is_none() {
+                    unmatched.push(offset);
+                }
+                1
+            }
+            c => c.len_utf8(),
+        };
+
+        offset += len;
+    }
+
+    unmatched.extend(open);
+    unmatched
+}
@@ -0,0 +1,165 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::Router;
+use serde_json::json;
+use tokio::net::{TcpListener, ToSocketAddrs};
+
+use crate::auth::{parse_basic, Authenticator};
+use crate::engine::{Engine, Output};
+use crate::session::Session;
+use crate::types::{Row, RowSet, Value};
+
+// Number of rows serialized at once when sending the result back
+const CHUNK_SIZE: usize = 1024;
+
+// Routes:
+//  POST /query, body is SQL text
+//
+// Responses:
+//  {"columns": [{"name": "a", "type": "int"}, ...], "rows": [[1], ...]}
+//  {"affected": 2}
+//  {"error": "No such table"}
+//
+// Servers with authenticator expect "Authorization: Basic ..." header and respond with
+// 401 Unauthorized if it's missing or not valid
+pub fn router(engine: Arc<Engine>) -> Router {
+    routes(Server {
+        engine,
+        authenticator: None,
+    })
+}
+
+pub fn router_with_auth(engine: Arc<Engine>, authenticator: Arc<dyn Authenticator>) -> Router {
+    routes(Server {
+        engine,
+        authenticator: Some(authenticator),
+    })
+}
+
+#[derive(Clone)]
+struct Server {
+    engine: Arc<Engine>,
+    // None => all requests are executed by anonymous session
+    authenticator: Option<Arc<dyn Authenticator>>,
+}
+
+fn routes(server: Server) -> Router {
+    Router::new()
+        .route("/query", post(query))
+        .with_state(server)
+}
+
+pub async fn serve(engine: Arc<Engine>, addr: impl ToSocketAddrs) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    axum::serve(listener, router(engine)).await
+}
+
+async fn query(State(server): State<Server>, headers: HeaderMap, sql: String) -> Response {
+    let credentials = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_basic);
+    let session = match (&server.authenticator, credentials) {
+        (None, _) => Session::new(server.engine),
+        (Some(authenticator), Some((user, password))) => {
+            match Session::authenticate(server.engine, &**authenticator, &user, &password) {
+                Ok(session) => session,
+                Err(e) => return unauthorized(&e.to_string()),
+            }
+        }
+        (Some(_), None) => return unauthorized("Missing credentials"),
+    };
+    let mut session = session.single_request();
+
+    // engine is blocking, don't stall the runtime
+    let output = tokio::task::spawn_blocking(move || session.execute(&sql, &[])).await;
+    match output {
+        Ok(Ok(Output::Affected(n))) => json_response(StatusCode::OK, json!({ "affected": n })),
+        Ok(Ok(Output::Rows(rowset))) => stream_rows(rowset),
+        Ok(Err(e)) => json_response(StatusCode::BAD_REQUEST, json!({ "error": e.to_string() })),
+        Err(e) => json_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            json!({ "error": e.to_string() }),
+        ),
+    }
+}
+
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response {
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/json")],
+        body.to_string(),
+    )
+        .into_response()
+}
+
+fn unauthorized(error: &str) -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        [
+            (header::CONTENT_TYPE, "application/json"),
+            (header::WWW_AUTHENTICATE, "Basic realm=\"camellia\""),
+        ],
+        json!({ "error": error }).to_string(),
+    )
+        .into_response()
+}
+
+// Serializes rows lazily, CHUNK_SIZE rows at a time, so large results
+// are not rendered into a single buffer.
+// NOTE: the RowSet itself is fully materialized by Session::execute,
+// only JSON encoding is streamed
+fn stream_rows(rowset: RowSet) -> Response {
+    let RowSet { schema, rows } = rowset;
+    let columns: Vec<_> = schema
+        .columns()
+        .map(|column| json!({ "name": column.name, "type": column.type_.to_string() }))
+        .collect();
+    let head = format!("{{\"columns\":{},\"rows\":[", serde_json::Value::Array(columns));
+
+    let mut rows = rows.into_iter();
+    let mut first = true;
+    let body = std::iter::from_fn(move || {
+        let mut chunk = String::new();
+        for row in rows.by_ref().take(CHUNK_SIZE) {
+            if !std::mem::take(&mut first) {
+                chunk.push(',');
+            }
+            chunk.push_str(&row_to_json(&row).to_string());
+        }
+
+        (!chunk.is_empty()).then_some(chunk)
+    });
+
+    let chunks = std::iter::once(head)
+        .chain(body)
+        .chain(std::iter::once("]}".to_owned()))
+        .map(Ok::<_, Infallible>);
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/json")],
+        Body::from_stream(futures::stream::iter(chunks)),
+    )
+        .into_response()
+}
+
+fn row_to_json(row: &Row) -> serde_json::Value {
+    let values = row
+        .values()
+        .map(|value| match value {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(val) => serde_json::Value::Bool(*val),
+            Value::Int(val) => serde_json::Value::from(*val),
+            Value::String(val) => serde_json::Value::String(val.clone()),
+        })
+        .collect();
+
+    serde_json::Value::Array(values)
+}
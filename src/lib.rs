@@ -1,10 +1,38 @@
+mod access;
+mod auth;
+mod dialect;
 mod engine;
+mod env;
+mod error;
 mod expression;
 mod ops;
 mod schema;
+mod storage;
 mod table;
+mod table_function;
 mod types;
 
-pub use crate::engine::{Engine, Output};
+pub mod bench;
+pub mod compat;
+pub mod session;
+pub mod trace;
+
+#[cfg(feature = "difftest")]
+pub mod difftest;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "http")]
+pub mod http;
+
+pub use crate::access::{Access, AccessHook};
+pub use crate::auth::{Authenticator, Permissions, StatementClass, StaticUsers};
+pub use crate::dialect::Dialect;
+pub use crate::engine::{Engine, EngineBuilder, Output, Stats};
+pub use crate::env::Env;
+pub use crate::error::Error;
+pub use crate::ops::Plan;
 pub use crate::schema::{Schema, Column, Type};
-pub use crate::types::{RowSet, Value};
+pub use crate::table_function::{Batches, GenerateSeries, ReadCsv, TableFunction};
+pub use crate::types::{Row, RowSet, Value};
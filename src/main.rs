@@ -1,74 +1,467 @@
 use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use minitrace::collector::SpanContext;
-use rustyline::DefaultEditor;
+use clap::{Parser, Subcommand};
+use log::LevelFilter;
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::Editor;
 
-mod engine;
-mod expression;
-mod ops;
-mod schema;
-mod table;
-mod trace;
-mod types;
+mod config;
+mod helper;
+mod output;
+mod pager;
+mod script;
 
-use engine::{Engine, Output};
+use camellia::session::Session;
+use camellia::{bench, trace, Dialect, Engine, Output, ReadCsv, Stats};
+use config::Settings;
+use helper::Helper;
 
-const HISTORY_PATH: &str = "history.txt";
+#[derive(Parser)]
+#[command(
+    version,
+    about = "camellia SQL shell",
+    args_conflicts_with_subcommands = true
+)]
+struct Args {
+    #[command(subcommand)]
+    subcommand: Option<Command>,
+
+    /// Path to the database, created if missing
+    #[arg(default_value = "camellia.db")]
+    database: PathBuf,
+
+    /// Run SQL and exit, exit code is non-zero if any statement fails
+    #[arg(short = 'c', long = "command", conflicts_with = "file")]
+    command: Option<String>,
+
+    /// Run SQL script and exit, exit code is non-zero if any statement fails
+    #[arg(long)]
+    file: Option<PathBuf>,
+
+    /// Reject statements which modify the database
+    #[arg(long)]
+    readonly: bool,
+
+    /// SQL dialect of statements: generic, sqlite, postgres or mysql
+    #[arg(long, default_value_t = Dialect::Generic)]
+    dialect: Dialect,
+
+    /// Path to the config file with REPL settings, [default: $XDG_CONFIG_HOME/camellia/config]
+    #[arg(long)]
+    config: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Measure scan, filter, sort, point lookup and insert over synthetic dataset
+    Bench {
+        /// Number of rows in the dataset
+        #[arg(long, default_value_t = 100_000)]
+        rows: usize,
+
+        /// Number of runs of each workload
+        #[arg(long, default_value_t = 10)]
+        iterations: u32,
+
+        /// Use in-memory storage instead of temporary RocksDB database
+        #[arg(long)]
+        in_memory: bool,
+    },
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+    let args = Args::parse();
+
     if let Err(e) = dotenvy::dotenv() {
         eprintln!("Faield to read .env file: {}", e);
     }
 
+    // engine logs are filtered by RUST_LOG, e.g. RUST_LOG=camellia::txn=debug,
+    // otherwise only warnings are printed until :log on, which lets through
+    // debug logs of camellia but not of the http/grpc stack
+    let filter = env_logger::Env::default().default_filter_or("warn,camellia=trace");
+    env_logger::Builder::from_env(filter).init();
+    if std::env::var_os("RUST_LOG").is_none() {
+        log::set_max_level(LevelFilter::Warn);
+    }
+
     if let Ok(endpoint) = std::env::var("CAMELLIA_TRACE") {
-        if let Err(e) = trace::init(endpoint) {
+        // fraction of traced statements, all by default
+        let sample_ratio = match std::env::var("CAMELLIA_TRACE_SAMPLE") {
+            Ok(ratio) => ratio.parse()?,
+            Err(_) => 1.0,
+        };
+        if let Err(e) = trace::init_otlp(endpoint, sample_ratio) {
             eprintln!("Failed to initialize tracing: {}", e)
         }
     }
 
-    let mut rl = DefaultEditor::new()?;
-    if let Err(e) = rl.load_history(HISTORY_PATH) {
-        println!("Failed to load history: {}", e);
+    if let Some(Command::Bench {
+        rows,
+        iterations,
+        in_memory,
+    }) = args.subcommand
+    {
+        return run_bench(rows, iterations, in_memory);
     }
 
-    let engine = Engine::new("camellia.db")?;
-    while let Ok(line) = rl.readline("> ") {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
+    let engine = Engine::builder()
+        .readonly(args.readonly)
+        .dialect(args.dialect)
+        .table_function("read_csv", Arc::new(ReadCsv))
+        .open(&args.database)?;
+    let engine = Arc::new(engine);
+    // dialect changed with SET dialect lasts until exit
+    let mut session = Session::new(engine.clone());
+
+    let mut settings = Settings::default();
+    // config is only read, settings changed by dot-commands last until exit
+    let config_path = args.config.or_else(config::config_path);
+    if let Some(path) = &config_path {
+        load_config(&mut session, &mut settings, path);
+    }
+
+    let result = match (args.command, args.file) {
+        (Some(sql), _) => Some(run_script(&mut session, &mut settings, "command", &sql)),
+        (None, Some(path)) => Some(run_file(&mut session, &mut settings, &path)),
+        (None, None) => None,
+    };
+
+    if let Some(result) = result {
+        drop(session);
+        drop(engine);
+        trace::shutdown();
+        if let Err(e) = result {
+            eprintln!("{}", e);
+            std::process::exit(1);
         }
+        return Ok(());
+    }
+
+    let mut rl = Editor::<Helper, DefaultHistory>::new()?;
+    rl.set_helper(Some(Helper::new(engine.clone())));
+    let history_path = config::history_path();
+    if let Some(path) = &history_path {
+        // history doesn't exist on the first run
+        if path.exists() {
+            if let Err(e) = rl.load_history(path) {
+                println!("Failed to load history: {}", e);
+            }
+        }
+    }
 
-        if line == ":log on" {
-            engine.set_log(true);
+    // statement is accumulated here until terminating semicolon
+    let mut buffer = String::new();
+    loop {
+        let prompt = if buffer.is_empty() { "> " } else { ". " };
+        let line = match rl.readline(prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) if !buffer.is_empty() => {
+                // discard unfinished statement
+                buffer.clear();
+                continue;
+            }
+            Err(_) => break,
+        };
+
+        if buffer.is_empty() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('.') || line.starts_with(':') {
+                if let Err(e) = run_command(&mut session, &mut settings, line) {
+                    println!("{}", e);
+                }
+                rl.add_history_entry(line)?;
+                continue;
+            }
+        }
+
+        buffer.push_str(&line);
+        buffer.push('\n');
+        if !script::is_complete(&buffer, session.dialect()) {
             continue;
-        } else if line == ":log off" {
-            engine.set_log(false);
+        }
+
+        let statement = std::mem::take(&mut buffer);
+        let line = statement.trim();
+        if let Err(e) = execute(&mut session, &mut settings, line) {
+            print_error_position(line, &*e);
+            println!("Query failed: {}", e);
+        }
+
+        rl.add_history_entry(line)?;
+    }
+
+    trace::shutdown();
+    if let Some(path) = &history_path {
+        if let Err(e) = save(path, |path| Ok(rl.save_history(path)?)) {
+            println!("Failed to save history: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn run_bench(
+    rows: usize,
+    iterations: u32,
+    in_memory: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let dir = std::env::temp_dir().join(format!("camellia-bench-{}", std::process::id()));
+    let engine = if in_memory {
+        Engine::in_memory()
+    } else {
+        Engine::builder().open(&dir)?
+    };
+
+    println!(
+        "{} row(s), {} iteration(s), {} storage",
+        rows,
+        iterations,
+        if in_memory { "memory" } else { "rocksdb" }
+    );
+    let result = bench::run(&engine, rows, iterations);
+    drop(engine);
+    if !in_memory {
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            eprintln!("Failed to remove {}: {}", dir.display(), e);
+        }
+    }
+
+    for m in result? {
+        println!(
+            "{:<14} {:>12.3?} {:>14.0} rows/s",
+            m.name,
+            m.mean(),
+            m.rows_per_sec()
+        );
+    }
+    Ok(())
+}
+
+// Run dot-commands from the config file, missing file means default settings
+fn load_config(session: &mut Session, settings: &mut Settings, path: &Path) {
+    let config = match std::fs::read_to_string(path) {
+        Ok(config) => config,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    for (i, line) in config.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
             continue;
         }
 
-        let span = minitrace::Span::root("query", SpanContext::random())
-            .with_property(|| ("query", line.to_owned()));
-        let _guard = span.set_local_parent();
+        if let Err(e) = run_command(session, settings, line) {
+            eprintln!("{}:{}: {}", path.display(), i + 1, e);
+        }
+    }
+}
+
+// Call |write| after creating parent directories of |path|
+fn save<F>(path: &Path, write: F) -> Result<(), Box<dyn Error + Send + Sync>>
+where
+    F: FnOnce(&Path) -> Result<(), Box<dyn Error + Send + Sync>>,
+{
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    write(path)
+}
+
+fn execute(
+    session: &mut Session,
+    settings: &mut Settings,
+    sql: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (output, stats) = session.execute_with_stats(sql)?;
+    print_output(output, settings)?;
+    if settings.timing {
+        print_stats(&stats);
+    }
+
+    Ok(())
+}
+
+// Underline the token at which |sql| failed to parse, e.g.
+//
+//  select 1 + from t;
+//             ^^^^
+fn print_error_position(sql: &str, e: &(dyn Error + Send + Sync + 'static)) {
+    let Some(camellia::Error::Parse {
+        line,
+        column,
+        token,
+        ..
+    }) = e.downcast_ref()
+    else {
+        return;
+    };
+
+    let Some(text) = sql.lines().nth(line.saturating_sub(1) as usize) else {
+        return;
+    };
+    let width = token
+        .as_ref()
+        .map_or(1, |token| token.chars().count().max(1));
+    println!("{}", text);
+    println!(
+        "{}{}",
+        " ".repeat(column.saturating_sub(1) as usize),
+        "^".repeat(width)
+    );
+}
 
-        match engine.run_sql(line) {
-            Ok(Output::Affected(n)) => {
-                if n != 0 {
-                    println!("{} row(s) affected", n);
+fn print_output(
+    output: Output,
+    settings: &mut Settings,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match output {
+        Output::Affected(n) => {
+            if n != 0 {
+                println!("{} row(s) affected", n);
+            }
+        }
+        Output::Rows(rowset) if settings.output.is_some() => {
+            // files get complete result, .maxrows and pager are for terminal only
+            if let Some(redirect) = &mut settings.output {
+                output::write(
+                    &mut redirect.file,
+                    &rowset,
+                    settings.mode,
+                    &settings.nullvalue,
+                )
+                .map_err(|e| format!("Failed to write {}: {}", redirect.path.display(), e))?;
+                if redirect.once {
+                    settings.output = None;
                 }
             }
-            Ok(Output::Rows(rowset)) => {
-                println!("{}", rowset);
+        }
+        Output::Rows(mut rowset) => {
+            let total = rowset.rows.len();
+            if let Some(maxrows) = settings.maxrows {
+                rowset.rows.truncate(maxrows);
+            }
+
+            let mut text = Vec::new();
+            // writing to Vec doesn't fail
+            let _ = output::write(&mut text, &rowset, settings.mode, &settings.nullvalue);
+            let mut text = String::from_utf8_lossy(&text).trim_end().to_owned();
+            if rowset.rows.len() < total {
+                let omitted = total - rowset.rows.len();
+                text.push_str(&format!("\n... {} more row(s), see .maxrows", omitted));
             }
-            Err(e) => {
-                println!("Query failed: {}", e);
+
+            if settings.pager {
+                pager::show(&text);
+            } else {
+                println!("{}", text);
             }
         }
+    }
 
-        rl.add_history_entry(line)?;
+    Ok(())
+}
+
+fn print_stats(stats: &Stats) {
+    println!(
+        "Run Time: parse {:?}, plan {:?}, execute {:?}, {} row(s)",
+        stats.parse, stats.plan, stats.execute, stats.rows
+    );
+}
+
+fn run_file(
+    session: &mut Session,
+    settings: &mut Settings,
+    path: &Path,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let sql = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    run_script(session, settings, &path.display().to_string(), &sql)
+}
+
+// Run statements of the script one by one, stops at first failed statement.
+// |source| is used in error messages to point at the script
+fn run_script(
+    session: &mut Session,
+    settings: &mut Settings,
+    source: &str,
+    sql: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let statements =
+        script::split(sql, session.dialect()).map_err(|e| format!("{}: {}", source, e))?;
+    for statement in statements {
+        if let Err(e) = execute(session, settings, &statement.sql) {
+            return Err(format!("{}:{}: Query failed: {}", source, statement.line, e).into());
+        }
+    }
+
+    Ok(())
+}
+
+// Handle REPL-only commands, e.g. .tables, .schema [table], .read file, .mode csv
+fn run_command(
+    session: &mut Session,
+    settings: &mut Settings,
+    command: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut args = command.split_whitespace();
+    match (args.next(), args.next(), args.next()) {
+        // statements, plans and commits, see RUST_LOG for finer control
+        (Some(":log"), Some("on"), None) => log::set_max_level(LevelFilter::Debug),
+        (Some(":log"), Some("off"), None) => log::set_max_level(LevelFilter::Warn),
+        (Some(".tables"), None, None) => {
+            for table in session.engine().tables()? {
+                println!("{}", table);
+            }
+        }
+        (Some(".schema"), table, None) => {
+            let tables = match table {
+                Some(table) => vec![table.to_owned()],
+                None => session.engine().tables()?,
+            };
+
+            for table in tables {
+                let schema = session.engine().schema(&table)?;
+                println!("{};", schema.to_create_table(&table));
+            }
+        }
+        (Some(".read"), Some(path), None) => run_file(session, settings, Path::new(path))?,
+        (Some(".timing"), Some("on"), None) => settings.timing = true,
+        (Some(".timing"), Some("off"), None) => settings.timing = false,
+        (Some(".pager"), Some("on"), None) => settings.pager = true,
+        (Some(".pager"), Some("off"), None) => settings.pager = false,
+        (Some(".maxrows"), Some(n), None) => {
+            let n: usize = n.parse().map_err(|e| format!("Invalid .maxrows: {}", e))?;
+            // 0 => unlimited
+            settings.maxrows = (n != 0).then_some(n);
+        }
+        (Some(".output"), None, None) => settings.output = None,
+        (Some(".output"), Some(path), None) => {
+            settings.output = Some(output::Redirect::create(Path::new(path), false)?)
+        }
+        (Some(".once"), Some(path), None) => {
+            settings.output = Some(output::Redirect::create(Path::new(path), true)?)
+        }
+        (Some(".mode"), Some(mode), None) => settings.mode = mode.parse()?,
+        (Some(".nullvalue"), Some(text), None) => {
+            settings.nullvalue = match text {
+                "\"\"" => String::new(),
+                text => text.to_owned(),
+            };
+        }
+        _ => return Err(format!("Unknown command: {}", command).into()),
     }
 
-    trace::shutdown();
-    rl.save_history(HISTORY_PATH)?;
     Ok(())
 }
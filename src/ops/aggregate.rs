@@ -0,0 +1,435 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use sqlparser::ast;
+
+use super::distinct::DistinctSet;
+use super::sort::{Key, KeyPart, Order};
+use super::{Operation, Output, Plan};
+use crate::error::Error;
+use crate::expression::Expression;
+use crate::schema::{Column, Schema, Type};
+use crate::types::{Result, Row, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Count,
+    Sum,
+    Min,
+    Max,
+    // string_agg(expr, separator), a.k.a. group_concat(expr [, separator])
+    StringAgg,
+}
+
+impl Function {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let function = match name {
+            "count" => Function::Count,
+            "sum" => Function::Sum,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "string_agg" | "group_concat" => Function::StringAgg,
+            _ => return None,
+        };
+
+        Some(function)
+    }
+}
+
+// Aggregate function call, arguments are evaluated over input rows
+#[derive(Clone)]
+pub struct Call {
+    function: Function,
+    // aggregate only distinct values of the first argument, e.g. count(DISTINCT a)
+    distinct: bool,
+    // empty for count(*)
+    args: Vec<Expression>,
+    // ORDER BY within the call, e.g. string_agg(name, ',' ORDER BY id)
+    order_by: Vec<(Expression, Order)>,
+}
+
+impl Call {
+    pub fn parse(call: ast::Function, schema: &Schema) -> Result<Self> {
+        let name = call.name.to_string().to_ascii_lowercase();
+        let function =
+            Function::from_name(&name).ok_or_else(|| format!("Unknown function: {}", name))?;
+
+        match call {
+            ast::Function {
+                filter: None,
+                null_treatment: None,
+                over: None,
+                special: false,
+                ..
+            } => {}
+            _ => return Err(format!("Unsupported form of {} aggregate", name).into()),
+        }
+
+        let mut args = Vec::with_capacity(call.args.len());
+        let mut star = false;
+        for arg in call.args {
+            match arg {
+                ast::FunctionArg::Unnamed(ast::FunctionArgExpr::Expr(e)) => {
+                    args.push(Expression::parse(e, schema)?)
+                }
+                ast::FunctionArg::Unnamed(ast::FunctionArgExpr::Wildcard) => star = true,
+                _ => return Err("Unsupported function arg kind".into()),
+            }
+        }
+
+        let valid = match function {
+            Function::Count if star => args.is_empty() && !call.distinct,
+            Function::StringAgg if name == "group_concat" => !star && !args.is_empty(),
+            Function::StringAgg => !star && args.len() == 2,
+            _ => !star && args.len() == 1,
+        };
+        if !valid || args.len() > 2 {
+            return Err(format!("Invalid number of arguments for {} function", name).into());
+        }
+        if call.distinct && args.len() != 1 {
+            return Err("DISTINCT aggregates must have exactly one argument".into());
+        }
+        if function == Function::StringAgg && args.len() == 1 {
+            args.push(Expression::Const(Value::String(",".to_owned())));
+        }
+
+        let mut order_by = Vec::with_capacity(call.order_by.len());
+        for expr in call.order_by {
+            let order = Order::new(expr.asc, expr.nulls_first);
+            order_by.push((Expression::parse(expr.expr, schema)?, order));
+        }
+        if !order_by.is_empty() && function != Function::StringAgg {
+            return Err(format!("ORDER BY is not supported in {} function", name).into());
+        }
+
+        Ok(Call {
+            function,
+            distinct: call.distinct,
+            args,
+            order_by,
+        })
+    }
+
+    pub fn result_type(&self, schema: &Schema) -> Result<Type> {
+        let arg_types = self
+            .args
+            .iter()
+            .map(|arg| arg.result_type(schema))
+            .collect::<Result<Vec<_>>>()?;
+
+        match self.function {
+            Function::Count => Ok(Type::Integer),
+            Function::Sum => {
+                if !arg_types[0].convertable_to(Type::Integer) {
+                    return Err(format!("Cannot sum values of type {}", arg_types[0]).into());
+                }
+                Ok(Type::Integer)
+            }
+            Function::Min | Function::Max => Ok(arg_types[0]),
+            // values of any type are converted to text
+            Function::StringAgg => Ok(Type::Text),
+        }
+    }
+
+    fn describe(&self, schema: &Schema) -> String {
+        let name = match self.function {
+            Function::Count => "count",
+            Function::Sum => "sum",
+            Function::Min => "min",
+            Function::Max => "max",
+            Function::StringAgg => "string_agg",
+        };
+
+        let mut args: Vec<_> = self
+            .args
+            .iter()
+            .map(|arg| arg.display(schema).to_string())
+            .collect();
+        if args.is_empty() {
+            args.push("*".to_owned());
+        }
+
+        let distinct = if self.distinct { "DISTINCT " } else { "" };
+        let mut call = format!("{}({}{}", name, distinct, args.join(", "));
+        if !self.order_by.is_empty() {
+            let order_by: Vec<_> = self
+                .order_by
+                .iter()
+                .map(|(e, order)| order.describe(e.display(schema).to_string()))
+                .collect();
+            call.push_str(" ORDER BY ");
+            call.push_str(&order_by.join(", "));
+        }
+        call.push(')');
+        call
+    }
+}
+
+// Running state of a call within a group
+struct Accumulator {
+    state: State,
+    // values of the first argument seen by DISTINCT call
+    seen: Option<DistinctSet>,
+}
+
+impl Accumulator {
+    fn new(call: &Call) -> Self {
+        Accumulator {
+            state: State::new(call),
+            seen: call.distinct.then(DistinctSet::new),
+        }
+    }
+
+    fn update(&mut self, call: &Call, row: &Row) -> Result<()> {
+        let mut args = Vec::with_capacity(call.args.len());
+        for arg in &call.args {
+            args.push(arg.eval(row)?);
+        }
+
+        if let Some(val) = args.first() {
+            // NULL arguments are skipped, same as in sqlite
+            if val.is_null() {
+                return Ok(());
+            }
+            if let Some(seen) = &mut self.seen {
+                if !seen.insert(val)? {
+                    return Ok(());
+                }
+            }
+        }
+        self.state.update(call, args, row)
+    }
+
+    fn finish(self) -> Value {
+        self.state.finish()
+    }
+}
+
+enum State {
+    Count(i64),
+    // None until first non-NULL value
+    Sum(Option<i64>),
+    Min(Value),
+    Max(Value),
+    // (ORDER BY key, separator, value)
+    StringAgg(Vec<(Key, String, String)>),
+}
+
+impl State {
+    fn new(call: &Call) -> Self {
+        match call.function {
+            Function::Count => State::Count(0),
+            Function::Sum => State::Sum(None),
+            Function::Min => State::Min(Value::Null),
+            Function::Max => State::Max(Value::Null),
+            Function::StringAgg => State::StringAgg(Vec::new()),
+        }
+    }
+
+    // |args| are values of call arguments for |row|
+    fn update(&mut self, call: &Call, args: Vec<Value>, row: &Row) -> Result<()> {
+        let mut args = args.into_iter();
+        match self {
+            State::Count(n) => *n += 1,
+            State::Sum(sum) => {
+                let val = args.next().ok_or("Missing aggregate argument")?;
+                let val = val.to_int().ok_or("Cannot convert sum argument to INT")?;
+                let total = sum.unwrap_or(0).checked_add(val);
+                *sum = Some(total.ok_or(Error::Overflow("SUM"))?);
+            }
+            State::Min(min) => {
+                let val = args.next().ok_or("Missing aggregate argument")?;
+                if min.is_null() || val < *min {
+                    *min = val;
+                }
+            }
+            State::Max(max) => {
+                let val = args.next().ok_or("Missing aggregate argument")?;
+                if max.is_null() || val > *max {
+                    *max = val;
+                }
+            }
+            State::StringAgg(values) => {
+                let val = args.next().ok_or("Missing aggregate argument")?.to_string();
+                let separator = match args.next() {
+                    Some(Value::Null) | None => String::new(),
+                    Some(separator) => separator.to_string(),
+                };
+
+                let mut key = Vec::with_capacity(call.order_by.len());
+                for (e, order) in &call.order_by {
+                    key.push(KeyPart {
+                        value: e.eval(row)?,
+                        order: *order,
+                    });
+                }
+                values.push((key, separator, val));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) -> Value {
+        match self {
+            State::Count(n) => Value::Int(n),
+            State::Sum(sum) => sum.map_or(Value::Null, Value::Int),
+            State::Min(val) | State::Max(val) => val,
+            State::StringAgg(mut values) => {
+                if values.is_empty() {
+                    return Value::Null;
+                }
+
+                // stable, so rows with equal keys keep input order
+                values.sort_by(|a, b| a.0.cmp(&b.0));
+                let mut result = String::new();
+                for (i, (_, separator, val)) in values.into_iter().enumerate() {
+                    // separator of the first value is not used
+                    if i > 0 {
+                        result.push_str(&separator);
+                    }
+                    result.push_str(&val);
+                }
+                Value::String(result)
+            }
+        }
+    }
+}
+
+// Groups input rows by |group_by| and emits (group_by..., calls...) row per group, ordered
+// by group key. Without GROUP BY single row is emitted even for empty input
+pub struct Aggregate<'txn> {
+    inner: Box<dyn Operation + 'txn>,
+
+    group_by: Vec<Expression>,
+    calls: Vec<Call>,
+    schema: Schema,
+
+    // None until input is consumed
+    results: Option<std::vec::IntoIter<Row>>,
+}
+
+impl<'txn> Aggregate<'txn> {
+    // |names| are column names of the output, one per group_by expression and call
+    pub fn new(
+        group_by: Vec<Expression>,
+        calls: Vec<Call>,
+        names: Vec<String>,
+        inner: Box<dyn Operation + 'txn>,
+    ) -> Result<Self> {
+        let input = inner.schema();
+        let mut types = Vec::with_capacity(names.len());
+        for e in &group_by {
+            types.push(e.result_type(input)?);
+        }
+        for call in &calls {
+            types.push(call.result_type(input)?);
+        }
+
+        let schema = Schema {
+            primary_key: None,
+            strict: false,
+            columns: names
+                .into_iter()
+                .zip(types)
+                .map(|(name, type_)| Column { name, type_ })
+                .collect(),
+        };
+
+        Ok(Aggregate {
+            inner,
+            group_by,
+            calls,
+            schema,
+            results: None,
+        })
+    }
+
+    #[minitrace::trace]
+    fn aggregate(&mut self) -> Result<Vec<Row>> {
+        let mut groups: BTreeMap<Row, Vec<Accumulator>> = BTreeMap::new();
+        if self.group_by.is_empty() {
+            let states = self.calls.iter().map(Accumulator::new).collect();
+            groups.insert(Row::from(Vec::new()), states);
+        }
+
+        loop {
+            let batch = match self.inner.poll()? {
+                Output::Batch(batch) => batch,
+                Output::Finished => break,
+            };
+
+            for row in batch {
+                let mut key = Vec::with_capacity(self.group_by.len());
+                for e in &self.group_by {
+                    key.push(e.eval(&row)?);
+                }
+
+                let calls = &self.calls;
+                let states = groups
+                    .entry(Row::from(key))
+                    .or_insert_with(|| calls.iter().map(Accumulator::new).collect());
+                for (state, call) in states.iter_mut().zip(calls) {
+                    state.update(call, &row)?;
+                }
+            }
+        }
+
+        let rows = groups
+            .into_iter()
+            .map(|(key, states)| {
+                let mut values: Vec<Value> = key.into_values().collect();
+                values.extend(states.into_iter().map(Accumulator::finish));
+                Row::from(values)
+            })
+            .collect();
+        Ok(rows)
+    }
+}
+
+impl<'txn> Operation for Aggregate<'txn> {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn explain(&self) -> Plan {
+        let input = self.inner.schema();
+        let calls: Vec<_> = self.calls.iter().map(|c| c.describe(input)).collect();
+        let mut details = calls.join(", ");
+        if !self.group_by.is_empty() {
+            let group_by: Vec<_> = self
+                .group_by
+                .iter()
+                .map(|e| e.display(input).to_string())
+                .collect();
+            details = format!("{} GROUP BY {}", details, group_by.join(", "));
+        }
+
+        Plan::new("Aggregate")
+            .details(details.trim_start().to_owned())
+            .input(self.inner.explain())
+    }
+
+    #[minitrace::trace]
+    fn poll(&mut self) -> Result<Output> {
+        const BATCH_SIZE: usize = 1024;
+
+        if self.results.is_none() {
+            self.results = Some(self.aggregate()?.into_iter());
+        }
+
+        let results = self.results.as_mut().unwrap();
+        let batch: Vec<Row> = results.by_ref().take(BATCH_SIZE).collect();
+        if batch.is_empty() {
+            return Ok(Output::Finished);
+        }
+
+        minitrace::Event::add_to_local_parent("batch", || {
+            [(
+                Cow::Borrowed("size"),
+                Cow::Owned(format!("{}", batch.len())),
+            )]
+        });
+        Ok(Output::Batch(batch))
+    }
+}
@@ -0,0 +1,180 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use minitrace::local::LocalSpan;
+
+use crate::types::{Result, Value};
+
+// Values kept in memory before they are written to disk as a sorted run
+const MAX_IN_MEMORY: usize = 4096;
+// Runs are merged into one when there are that many, so that lookups stay cheap
+const MAX_RUNS: usize = 8;
+// First value of every block of a run is indexed in memory
+const BLOCK_SIZE: usize = 128;
+
+// Values seen by DISTINCT aggregate call. Recent ones are kept in memory, older ones
+// in sorted runs on disk, so high-cardinality groups don't exhaust memory
+pub(super) struct DistinctSet {
+    memory: BTreeSet<Value>,
+    // disjoint with each other and with |memory|
+    runs: Vec<Run>,
+}
+
+impl DistinctSet {
+    pub fn new() -> Self {
+        DistinctSet {
+            memory: BTreeSet::new(),
+            runs: Vec::new(),
+        }
+    }
+
+    // Returns false if |value| was seen before
+    pub fn insert(&mut self, value: &Value) -> Result<bool> {
+        if self.memory.contains(value) {
+            return Ok(false);
+        }
+        for run in &self.runs {
+            if run.contains(value)? {
+                return Ok(false);
+            }
+        }
+
+        self.memory.insert(value.clone());
+        if self.memory.len() >= MAX_IN_MEMORY {
+            self.spill()?;
+        }
+        Ok(true)
+    }
+
+    fn spill(&mut self) -> Result<()> {
+        let _span = LocalSpan::enter_with_local_parent("distinct::spill")
+            .with_property(|| ("runs", self.runs.len().to_string()));
+        let memory = std::mem::take(&mut self.memory);
+        self.runs.push(Run::write(memory.into_iter().map(Ok))?);
+
+        if self.runs.len() >= MAX_RUNS {
+            let runs = std::mem::take(&mut self.runs);
+            self.runs.push(Run::merge(&runs)?);
+        }
+        Ok(())
+    }
+}
+
+// Sorted values in a temporary file, removed on drop
+struct Run {
+    path: PathBuf,
+    file: File,
+    len: usize,
+    // (first value, offset) of every block
+    index: Vec<(Value, u64)>,
+    end: u64,
+}
+
+impl Run {
+    fn write(values: impl Iterator<Item = Result<Value>>) -> Result<Self> {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        let n = NEXT.fetch_add(1, Ordering::Relaxed);
+        let name = format!("camellia-{}-distinct-{}.tmp", std::process::id(), n);
+        let path = std::env::temp_dir().join(name);
+
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let mut run = Run {
+            path,
+            file,
+            len: 0,
+            index: Vec::new(),
+            end: 0,
+        };
+
+        let mut writer = BufWriter::new(&run.file);
+        for value in values {
+            let value = value?;
+            if run.len % BLOCK_SIZE == 0 {
+                run.index.push((value.clone(), run.end));
+            }
+            let bytes = bincode::serialize(&value)?;
+            writer.write_all(&bytes)?;
+            run.end += bytes.len() as u64;
+            run.len += 1;
+        }
+        writer.flush()?;
+        drop(writer);
+
+        Ok(run)
+    }
+
+    // Runs are disjoint, so merged one keeps every value once
+    fn merge(runs: &[Run]) -> Result<Self> {
+        let mut sources = Vec::with_capacity(runs.len());
+        for run in runs {
+            sources.push(run.values()?);
+        }
+
+        let mut heap = BinaryHeap::new();
+        for (i, source) in sources.iter_mut().enumerate() {
+            if let Some(value) = source.next() {
+                heap.push(Reverse((value?, i)));
+            }
+        }
+
+        let merged = std::iter::from_fn(|| {
+            let Reverse((value, i)) = heap.pop()?;
+            match sources[i].next() {
+                Some(Ok(next)) => heap.push(Reverse((next, i))),
+                Some(Err(e)) => return Some(Err(e)),
+                None => {}
+            }
+            Some(Ok(value))
+        });
+        Run::write(merged)
+    }
+
+    fn contains(&self, value: &Value) -> Result<bool> {
+        // the only block which may contain |value|
+        let block = match self.index.partition_point(|(first, _)| first <= value) {
+            0 => return Ok(false),
+            n => n - 1,
+        };
+        let start = self.index[block].1;
+        let end = self
+            .index
+            .get(block + 1)
+            .map_or(self.end, |(_, offset)| *offset);
+
+        let mut bytes = vec![0; (end - start) as usize];
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut bytes)?;
+
+        let mut reader = &bytes[..];
+        while !reader.is_empty() {
+            let val: Value = bincode::deserialize_from(&mut reader)?;
+            if val >= *value {
+                return Ok(val == *value);
+            }
+        }
+        Ok(false)
+    }
+
+    fn values(&self) -> Result<impl Iterator<Item = Result<Value>> + '_> {
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(0))?;
+        let mut reader = BufReader::new(file);
+        Ok((0..self.len)
+            .map(move |_| -> Result<Value> { Ok(bincode::deserialize_from(&mut reader)?) }))
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
@@ -3,7 +3,7 @@ use std::borrow::Cow;
 use crate::schema::Schema;
 use crate::types::{Result, Row};
 
-use super::{Operation, Output};
+use super::{Operation, Output, Plan};
 
 // Source which produces single empty row
 pub struct Empty {
@@ -25,6 +25,10 @@ impl Operation for Empty {
         &self.schema
     }
 
+    fn explain(&self) -> Plan {
+        Plan::new("Empty")
+    }
+
     #[minitrace::trace]
     fn poll(&mut self) -> Result<Output> {
         match std::mem::replace(&mut self.finished, true) {
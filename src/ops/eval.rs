@@ -4,7 +4,7 @@ use crate::expression::Expression;
 use crate::schema::Schema;
 use crate::types::{Result, Row};
 
-use super::{Operation, Output};
+use super::{Operation, Output, Plan};
 
 pub struct Eval<'txn> {
     inner: Box<dyn Operation + 'txn>,
@@ -26,14 +26,14 @@ impl<'txn> Eval<'txn> {
         })
     }
 
-    fn eval_on(&self, row: &mut Row) {
+    fn eval_on(&self, row: &mut Row) -> Result<()> {
         // TODO: avoid allocation when possible?
         let mut mapped = Vec::with_capacity(self.expressions.len());
         for e in &self.expressions {
-            // TODO: handle errors
-            mapped.push(e.eval(row).unwrap());
+            mapped.push(e.eval(row)?);
         }
-        *row = Row::from(mapped)
+        *row = Row::from(mapped);
+        Ok(())
     }
 }
 
@@ -42,13 +42,24 @@ impl<'txn> Operation for Eval<'txn> {
         &self.schema
     }
 
+    fn explain(&self) -> Plan {
+        let expressions: Vec<_> = self
+            .expressions
+            .iter()
+            .map(|e| e.display(self.inner.schema()).to_string())
+            .collect();
+        Plan::new("Eval")
+            .details(expressions.join(", "))
+            .input(self.inner.explain())
+    }
+
     #[minitrace::trace]
     fn poll(&mut self) -> Result<Output> {
         let output = match self.inner.poll()? {
             Output::Finished => Output::Finished,
             Output::Batch(mut rows) => {
                 for row in rows.iter_mut() {
-                    self.eval_on(row);
+                    self.eval_on(row)?;
                 }
 
                 minitrace::Event::add_to_local_parent("batch", || {
@@ -2,10 +2,10 @@ use std::borrow::Cow;
 
 use sqlparser::ast::Expr;
 
-use super::{Operation, Output};
+use super::{Operation, Output, Plan};
 use crate::expression::Expression;
 use crate::schema::Schema;
-use crate::types::Result;
+use crate::types::{Result, Value};
 
 pub struct Filter<'txn> {
     inner: Box<dyn Operation + 'txn>,
@@ -26,14 +26,38 @@ impl<'txn> Operation for Filter<'txn> {
         self.inner.schema()
     }
 
+    fn explain(&self) -> Plan {
+        let filter = self.filter.display(self.inner.schema());
+        Plan::new("Filter")
+            .details(filter.to_string())
+            .input(self.inner.explain())
+    }
+
     #[minitrace::trace]
     fn poll(&mut self) -> Result<Output> {
         match self.inner.poll()? {
-            Output::Batch(mut batch) => {
-                // TODO: handle errors
-                batch.retain(|row| self.filter.eval(row).unwrap().to_bool().unwrap());
+            Output::Batch(batch) => {
+                let mut filtered = Vec::with_capacity(batch.len());
+                for row in batch {
+                    // NULL doesn't satisfy the condition
+                    let keep = match self.filter.eval(&row)? {
+                        Value::Null => false,
+                        val => val
+                            .to_bool()
+                            .ok_or("Cannot convert WHERE condition to BOOL")?,
+                    };
+
+                    if keep {
+                        filtered.push(row);
+                    }
+                }
+
+                let batch = filtered;
                 minitrace::Event::add_to_local_parent("batch", || {
-                    [(Cow::Borrowed("size"), Cow::Owned(format!("{}", batch.len())))]
+                    [(
+                        Cow::Borrowed("size"),
+                        Cow::Owned(format!("{}", batch.len())),
+                    )]
                 });
                 Ok(Output::Batch(batch))
             }
@@ -1,24 +1,55 @@
 use std::borrow::Cow;
 
-use rocksdb::{DBIteratorWithThreadMode, Transaction};
+use minitrace::local::LocalSpan;
 
 use crate::schema::Schema;
-use crate::types::{Database, Result, Row};
+use crate::storage::KeyValue;
+use crate::types::{Result, Row};
 
-use super::{Operation, Output};
+use super::{Operation, Output, Plan};
+
+const BATCH_SIZE: usize = 1024;
 
 // TODO: get rid of lifetimes?
 pub struct FullScan<'txn> {
+    table: String,
     schema: Schema,
-    iter: DBIteratorWithThreadMode<'txn, Transaction<'txn, Database>>,
+    // approximate row count of the table, see Table::estimated_rows
+    estimated_rows: u64,
+    iter: Box<dyn Iterator<Item = Result<KeyValue>> + 'txn>,
 }
 
 impl<'txn> FullScan<'txn> {
     pub fn new(
+        table: String,
         schema: Schema,
-        iter: DBIteratorWithThreadMode<'txn, Transaction<'txn, Database>>,
+        estimated_rows: u64,
+        iter: Box<dyn Iterator<Item = Result<KeyValue>> + 'txn>,
     ) -> Result<Self> {
-        Ok(FullScan { schema, iter })
+        Ok(FullScan {
+            table,
+            schema,
+            estimated_rows,
+            iter,
+        })
+    }
+
+    // Read up to BATCH_SIZE entries in separate span, so that storage time is
+    // distinguishable from deserialization
+    fn read_batch(&mut self) -> Result<Vec<KeyValue>> {
+        let span = LocalSpan::enter_with_local_parent("storage::iter");
+        let mut entries = Vec::with_capacity(BATCH_SIZE);
+        let mut bytes = 0;
+        for entry in self.iter.by_ref().take(BATCH_SIZE) {
+            let (key, value) = entry?;
+            bytes += key.len() + value.len();
+            entries.push((key, value));
+        }
+
+        let _span = span
+            .with_property(|| ("entries", entries.len().to_string()))
+            .with_property(|| ("bytes", bytes.to_string()));
+        Ok(entries)
     }
 }
 
@@ -27,35 +58,29 @@ impl<'txn> Operation for FullScan<'txn> {
         &self.schema
     }
 
+    fn explain(&self) -> Plan {
+        Plan::new("FullScan").details(format!("{}, ~{} row(s)", self.table, self.estimated_rows))
+    }
+
     #[minitrace::trace]
     fn poll(&mut self) -> Result<Output> {
-        const BATCH_SIZE: usize = 1024;
-
-        let mut batch = Vec::with_capacity(BATCH_SIZE);
-        loop {
-            match self.iter.next() {
-                Some(Ok((_key, value))) => {
-                    let row: Row = Row::deserialize(&value, &self.schema)?;
-                    batch.push(row);
-                    if batch.len() >= BATCH_SIZE {
-                        minitrace::Event::add_to_local_parent("batch", || {
-                            [(Cow::Borrowed("size"), Cow::Owned(format!("{}", batch.len())))]
-                        });
-                        return Ok(Output::Batch(batch));
-                    }
-                }
-                Some(Err(e)) => return Err(e.into()),
-                None => {
-                    if batch.is_empty() {
-                        return Ok(Output::Finished);
-                    } else {
-                        minitrace::Event::add_to_local_parent("batch", || {
-                            [(Cow::Borrowed("size"), Cow::Owned(format!("{}", batch.len())))]
-                        });
-                        return Ok(Output::Batch(batch));
-                    }
-                }
-            }
+        let entries = self.read_batch()?;
+        if entries.is_empty() {
+            return Ok(Output::Finished);
         }
+
+        let mut batch = Vec::with_capacity(entries.len());
+        for (_key, value) in entries {
+            let row: Row = Row::deserialize(&value, &self.schema)?;
+            batch.push(row);
+        }
+
+        minitrace::Event::add_to_local_parent("batch", || {
+            [(
+                Cow::Borrowed("size"),
+                Cow::Owned(format!("{}", batch.len())),
+            )]
+        });
+        Ok(Output::Batch(batch))
     }
 }
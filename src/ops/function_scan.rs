@@ -0,0 +1,64 @@
+use std::borrow::Cow;
+
+use super::{Operation, Output, Plan};
+use crate::schema::Schema;
+use crate::table_function::Batches;
+use crate::types::Result;
+
+// Rows produced by a table function, see TableFunction
+pub struct FunctionScan {
+    // call as written in the query, e.g. "generate_series(1, 10)"
+    call: String,
+    schema: Schema,
+    batches: Batches,
+}
+
+impl FunctionScan {
+    pub fn new(call: String, schema: Schema, batches: Batches) -> Self {
+        FunctionScan {
+            call,
+            schema,
+            batches,
+        }
+    }
+}
+
+impl Operation for FunctionScan {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn explain(&self) -> Plan {
+        Plan::new("FunctionScan").details(self.call.clone())
+    }
+
+    #[minitrace::trace]
+    fn poll(&mut self) -> Result<Output> {
+        // empty batches don't mean the end of rows
+        for batch in self.batches.by_ref() {
+            let batch = batch?;
+            // rows come from embedder code, later operators index them by schema
+            let width = self.schema.columns.len();
+            if let Some(row) = batch.iter().find(|row| row.len() != width) {
+                return Err(format!(
+                    "{} returned row of {} value(s), expected {}",
+                    self.call,
+                    row.len(),
+                    width
+                )
+                .into());
+            }
+            if !batch.is_empty() {
+                minitrace::Event::add_to_local_parent("batch", || {
+                    [(
+                        Cow::Borrowed("size"),
+                        Cow::Owned(format!("{}", batch.len())),
+                    )]
+                });
+                return Ok(Output::Batch(batch));
+            }
+        }
+
+        Ok(Output::Finished)
+    }
+}
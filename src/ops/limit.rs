@@ -0,0 +1,81 @@
+use std::borrow::Cow;
+
+use super::{Operation, Output, Plan};
+use crate::schema::Schema;
+use crate::types::Result;
+
+// Skips first |offset| rows and emits at most |limit| rows after them
+pub struct Limit<'txn> {
+    inner: Box<dyn Operation + 'txn>,
+
+    // rows left to skip
+    offset: usize,
+    // rows left to emit, None is unlimited
+    limit: Option<usize>,
+}
+
+impl<'txn> Limit<'txn> {
+    pub fn new(offset: usize, limit: Option<usize>, inner: Box<dyn Operation + 'txn>) -> Self {
+        Limit {
+            inner,
+            offset,
+            limit,
+        }
+    }
+}
+
+impl<'txn> Operation for Limit<'txn> {
+    fn schema(&self) -> &Schema {
+        self.inner.schema()
+    }
+
+    fn explain(&self) -> Plan {
+        let mut details = match self.limit {
+            Some(limit) => limit.to_string(),
+            None => "ALL".to_owned(),
+        };
+        if self.offset > 0 {
+            details = format!("{} OFFSET {}", details, self.offset);
+        }
+
+        Plan::new("Limit")
+            .details(details)
+            .input(self.inner.explain())
+    }
+
+    #[minitrace::trace]
+    fn poll(&mut self) -> Result<Output> {
+        loop {
+            // don't read rest of input once limit is reached
+            if self.limit == Some(0) {
+                return Ok(Output::Finished);
+            }
+
+            let mut batch = match self.inner.poll()? {
+                Output::Batch(batch) => batch,
+                Output::Finished => return Ok(Output::Finished),
+            };
+
+            let skip = std::cmp::min(self.offset, batch.len());
+            batch.drain(..skip);
+            self.offset -= skip;
+
+            if let Some(limit) = self.limit.as_mut() {
+                batch.truncate(*limit);
+                *limit -= batch.len();
+            }
+
+            if batch.is_empty() {
+                continue;
+            }
+
+            minitrace::Event::add_to_local_parent("batch", || {
+                [(
+                    Cow::Borrowed("size"),
+                    Cow::Owned(format!("{}", batch.len())),
+                )]
+            });
+            return Ok(Output::Batch(batch));
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use super::{Operation, Output, Plan};
+use crate::expression::Expression;
+use crate::schema::Schema;
+use crate::types::{Result, Row};
+
+// Skips |offset| first rows for each distinct value of |by| and emits at most |limit| next
+// ones (ClickHouse LIMIT n OFFSET m BY)
+pub struct LimitBy<'txn> {
+    inner: Box<dyn Operation + 'txn>,
+
+    limit: usize,
+    offset: usize,
+    by: Vec<Expression>,
+
+    // rows seen so far for each value of |by|
+    // TODO: use disk-backed storage for high-cardinality keys
+    counts: BTreeMap<Row, usize>,
+}
+
+impl<'txn> LimitBy<'txn> {
+    pub fn new(
+        limit: usize,
+        offset: usize,
+        by: Vec<Expression>,
+        inner: Box<dyn Operation + 'txn>,
+    ) -> Self {
+        LimitBy {
+            inner,
+            limit,
+            offset,
+            by,
+            counts: BTreeMap::new(),
+        }
+    }
+}
+
+impl<'txn> Operation for LimitBy<'txn> {
+    fn schema(&self) -> &Schema {
+        self.inner.schema()
+    }
+
+    fn explain(&self) -> Plan {
+        let by: Vec<_> = self
+            .by
+            .iter()
+            .map(|e| e.display(self.inner.schema()).to_string())
+            .collect();
+        let offset = match self.offset {
+            0 => String::new(),
+            offset => format!(" OFFSET {}", offset),
+        };
+        Plan::new("LimitBy")
+            .details(format!("{}{} BY {}", self.limit, offset, by.join(", ")))
+            .input(self.inner.explain())
+    }
+
+    #[minitrace::trace]
+    fn poll(&mut self) -> Result<Output> {
+        loop {
+            let batch = match self.inner.poll()? {
+                Output::Batch(batch) => batch,
+                Output::Finished => return Ok(Output::Finished),
+            };
+
+            let mut filtered = Vec::with_capacity(batch.len());
+            for row in batch {
+                let mut key = Vec::with_capacity(self.by.len());
+                for e in &self.by {
+                    key.push(e.eval(&row)?);
+                }
+
+                let count = self.counts.entry(Row::from(key)).or_insert(0);
+                if *count < self.offset.saturating_add(self.limit) {
+                    if *count >= self.offset {
+                        filtered.push(row);
+                    }
+                    *count += 1;
+                }
+            }
+
+            if filtered.is_empty() {
+                continue;
+            }
+
+            let batch = filtered;
+            minitrace::Event::add_to_local_parent("batch", || {
+                [(
+                    Cow::Borrowed("size"),
+                    Cow::Owned(format!("{}", batch.len())),
+                )]
+            });
+            return Ok(Output::Batch(batch));
+        }
+    }
+}
@@ -1,17 +1,28 @@
 use crate::schema::Schema;
 use crate::types::{Result, Row};
 
+mod aggregate;
+mod distinct;
 mod empty;
 mod eval;
 mod filter;
 mod fullscan;
+mod function_scan;
+mod limit;
+mod limit_by;
+mod plan;
 mod sort;
 mod values;
 
+pub use aggregate::{Aggregate, Call as AggregateCall, Function as AggregateFunction};
 pub use empty::Empty;
 pub use eval::Eval;
 pub use filter::Filter;
 pub use fullscan::FullScan;
+pub use function_scan::FunctionScan;
+pub use limit::Limit;
+pub use limit_by::LimitBy;
+pub use plan::Plan;
 pub use sort::Sort;
 pub use values::Values;
 
@@ -26,4 +37,7 @@ pub trait Operation {
 
     // Get next batch of rows
     fn poll(&mut self) -> Result<Output>;
+
+    // Describe this operator and its inputs, see Plan
+    fn explain(&self) -> Plan;
 }
@@ -0,0 +1,55 @@
+use std::fmt;
+
+// Description of operator tree, returned by EXPLAIN and Engine::explain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    // Operator name, e.g. "Filter"
+    pub operator: String,
+    // Operator arguments, e.g. filter condition
+    pub details: Option<String>,
+    pub inputs: Vec<Plan>,
+}
+
+impl Plan {
+    pub fn new(operator: &str) -> Self {
+        Plan {
+            operator: operator.to_owned(),
+            details: None,
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn details(mut self, details: String) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn input(mut self, input: Plan) -> Self {
+        self.inputs.push(input);
+        self
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}{}", "", self.operator, indent = depth * 2)?;
+        if let Some(details) = &self.details {
+            write!(f, ": {}", details)?;
+        }
+        writeln!(f)?;
+
+        for input in &self.inputs {
+            input.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+// One line per operator, inputs are indented under their consumer:
+//
+//  Eval: a, b
+//    Filter: a > 1
+//      FullScan: t
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
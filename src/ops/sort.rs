@@ -1,13 +1,79 @@
 use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 
 use sqlparser::ast;
 
-use super::{Operation, Output};
+use super::{Operation, Output, Plan};
 use crate::expression::Expression;
 use crate::schema::Schema;
 use crate::types::{Result, Row, Value};
 
+// Direction of ORDER BY term
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) struct Order {
+    desc: bool,
+    nulls_first: bool,
+}
+
+impl Order {
+    // Same as sqlite, NULL is smaller than any other value unless NULLS FIRST/LAST is given
+    pub(super) fn new(asc: Option<bool>, nulls_first: Option<bool>) -> Self {
+        let desc = asc == Some(false);
+        Order {
+            desc,
+            nulls_first: nulls_first.unwrap_or(!desc),
+        }
+    }
+
+    // ORDER BY |term| with this direction, as used in EXPLAIN
+    pub(super) fn describe(&self, mut term: String) -> String {
+        if self.desc {
+            term.push_str(" DESC");
+        }
+        // only non-default NULLS placement
+        if self.nulls_first == self.desc {
+            term.push_str(match self.nulls_first {
+                true => " NULLS FIRST",
+                false => " NULLS LAST",
+            });
+        }
+        term
+    }
+}
+
+// Value of ORDER BY term, ordered according to its direction
+#[derive(PartialEq, Eq)]
+pub(super) struct KeyPart {
+    pub(super) value: Value,
+    pub(super) order: Order,
+}
+
+impl PartialOrd for KeyPart {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KeyPart {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let nulls = match self.order.nulls_first {
+            true => Ordering::Less,
+            false => Ordering::Greater,
+        };
+
+        match (&self.value, &other.value) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Null, _) => nulls,
+            (_, Value::Null) => nulls.reverse(),
+            (a, b) if self.order.desc => b.cmp(a),
+            (a, b) => a.cmp(b),
+        }
+    }
+}
+
+pub(super) type Key = Vec<KeyPart>;
+
 enum State {
     Read,
     Merge,
@@ -19,7 +85,7 @@ pub struct Sort<'txn> {
     inner: Box<dyn Operation + 'txn>,
 
     // TODO: add specialization for single expression
-    by: Vec<Expression>,
+    by: Vec<(Expression, Order)>,
 
     // TODO: use disk-backed storage for runs
     runs: Vec<Vec<Row>>,
@@ -38,14 +104,7 @@ impl<'txn> Sort<'txn> {
         let schema = inner.schema();
         let mut expressions = Vec::with_capacity(order_by.len());
         for expr in order_by {
-            if let Some(false) = expr.asc {
-                return Err("DESC is not implemented".into());
-            }
-
-            if expr.nulls_first.is_some() {
-                return Err("NULLS FIRST is not implemented".into());
-            }
-
+            let order = Order::new(expr.asc, expr.nulls_first);
             let expr = Expression::parse(expr.expr, schema)?;
             let expr = match expr {
                 // ORDER BY allows to specify column by number instead of name
@@ -64,7 +123,7 @@ impl<'txn> Sort<'txn> {
                 }
                 e => e,
             };
-            expressions.push(expr);
+            expressions.push((expr, order));
         }
 
         Ok(Self {
@@ -78,13 +137,16 @@ impl<'txn> Sort<'txn> {
         })
     }
 
-    fn key_of(&self, row: &Row) -> Result<Row> {
+    fn key_of(&self, row: &Row) -> Result<Key> {
         let mut key = Vec::with_capacity(self.by.len());
-        for e in &self.by {
-            let val = e.eval(row)?;
-            key.push(val);
+        for (e, order) in &self.by {
+            let value = e.eval(row)?;
+            key.push(KeyPart {
+                value,
+                order: *order,
+            });
         }
-        Ok(Row::from(key))
+        Ok(key)
     }
 
     #[minitrace::trace]
@@ -92,9 +154,19 @@ impl<'txn> Sort<'txn> {
         loop {
             match self.inner.poll()? {
                 Output::Batch(mut batch) => {
-                    // TODO: handle errors
                     // TODO: batch can be small, use chunks of N
-                    batch.sort_by_cached_key(|row| self.key_of(row).unwrap());
+                    let mut error = None;
+                    batch.sort_by_cached_key(|row| match self.key_of(row) {
+                        Ok(key) => key,
+                        Err(e) => {
+                            error.get_or_insert(e);
+                            Vec::new()
+                        }
+                    });
+
+                    if let Some(e) = error {
+                        return Err(e);
+                    }
                     self.runs.push(batch);
                 }
                 Output::Finished => {
@@ -105,9 +177,9 @@ impl<'txn> Sort<'txn> {
     }
 
     // NOTE: consumes internals of |runs|
-    fn nway_merge(&self, runs: &mut [Vec<Row>]) -> Vec<Row> {
+    fn nway_merge(&self, runs: &mut [Vec<Row>]) -> Result<Vec<Row>> {
         struct Item {
-            key: Row,
+            key: Key,
             row: Row,
 
             iter: std::vec::IntoIter<Row>,
@@ -124,13 +196,13 @@ impl<'txn> Sort<'txn> {
         // we want min heap for sort
         #[allow(clippy::non_canonical_partial_ord_impl)]
         impl PartialOrd for Item {
-            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
                 Some(self.key.cmp(&other.key).reverse())
             }
         }
 
         impl Ord for Item {
-            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            fn cmp(&self, other: &Self) -> Ordering {
                 self.key.cmp(&other.key).reverse()
             }
         }
@@ -139,8 +211,7 @@ impl<'txn> Sort<'txn> {
         for run in runs.iter_mut() {
             let mut iter = std::mem::take(run).into_iter();
             if let Some(row) = iter.next() {
-                // TODO: handle error
-                let key = self.key_of(&row).unwrap();
+                let key = self.key_of(&row)?;
                 heap.push(Item { key, row, iter })
             }
         }
@@ -152,13 +223,12 @@ impl<'txn> Sort<'txn> {
 
             let mut iter = item.iter;
             if let Some(row) = iter.next() {
-                // TODO: handle error
-                let key = self.key_of(&row).unwrap();
+                let key = self.key_of(&row)?;
                 heap.push(Item { key, row, iter });
             }
         }
 
-        sorted
+        Ok(sorted)
     }
 
     #[minitrace::trace]
@@ -171,7 +241,7 @@ impl<'txn> Sort<'txn> {
         loop {
             const N: usize = 16;
             for chunk in runs.chunks_mut(N) {
-                let merged = self.nway_merge(chunk);
+                let merged = self.nway_merge(chunk)?;
                 self.runs.push(merged);
             }
 
@@ -193,7 +263,10 @@ impl<'txn> Sort<'txn> {
                     chunk.push(row);
                     if chunk.len() >= BATCH_SIZE {
                         minitrace::Event::add_to_local_parent("batch", || {
-                            [(Cow::Borrowed("size"), Cow::Owned(format!("{}", chunk.len())))]
+                            [(
+                                Cow::Borrowed("size"),
+                                Cow::Owned(format!("{}", chunk.len())),
+                            )]
                         });
                         return Ok(Output::Batch(chunk));
                     }
@@ -203,7 +276,10 @@ impl<'txn> Sort<'txn> {
                         return Ok(Output::Finished);
                     } else {
                         minitrace::Event::add_to_local_parent("batch", || {
-                            [(Cow::Borrowed("size"), Cow::Owned(format!("{}", chunk.len())))]
+                            [(
+                                Cow::Borrowed("size"),
+                                Cow::Owned(format!("{}", chunk.len())),
+                            )]
                         });
                         return Ok(Output::Batch(chunk));
                     }
@@ -218,6 +294,17 @@ impl<'txn> Operation for Sort<'txn> {
         self.inner.schema()
     }
 
+    fn explain(&self) -> Plan {
+        let by: Vec<_> = self
+            .by
+            .iter()
+            .map(|(e, order)| order.describe(e.display(self.inner.schema()).to_string()))
+            .collect();
+        Plan::new("Sort")
+            .details(by.join(", "))
+            .input(self.inner.explain())
+    }
+
     #[minitrace::trace]
     fn poll(&mut self) -> Result<Output> {
         loop {
@@ -1,4 +1,4 @@
-use super::{Operation, Output};
+use super::{Operation, Output, Plan};
 use crate::schema::Schema;
 use crate::types::{Result, Row};
 
@@ -22,6 +22,10 @@ impl Operation for Values {
         &self.schema
     }
 
+    fn explain(&self) -> Plan {
+        Plan::new("Values").details(format!("{} row(s)", self.values.len()))
+    }
+
     fn poll(&mut self) -> Result<Output> {
         const BATCH_SIZE: usize = 1024;
 
@@ -0,0 +1,116 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use camellia::{RowSet, Value};
+
+// Format of printed result sets, changed with .mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    // ascii-art table
+    Table,
+    // values separated by "|"
+    List,
+    // RFC 4180, with header
+    Csv,
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Mode::Table),
+            "list" => Ok(Mode::List),
+            "csv" => Ok(Mode::Csv),
+            _ => Err(format!("Unknown mode: {}, expected table, list or csv", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mode::Table => write!(f, "table"),
+            Mode::List => write!(f, "list"),
+            Mode::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+// Result sets redirected to a file with .output/.once
+pub struct Redirect {
+    pub path: PathBuf,
+    pub file: File,
+    // only next result set goes to the file
+    pub once: bool,
+}
+
+impl Redirect {
+    // Truncates existing file
+    pub fn create(path: &Path, once: bool) -> io::Result<Self> {
+        Ok(Redirect {
+            path: path.to_owned(),
+            file: File::create(path)?,
+            once,
+        })
+    }
+}
+
+// Write |rowset| to |out| in |mode|, NULLs are printed as |nullvalue|
+pub fn write(out: &mut dyn Write, rowset: &RowSet, mode: Mode, nullvalue: &str) -> io::Result<()> {
+    let header: Vec<_> = rowset
+        .schema
+        .columns()
+        .map(|column| column.name.clone())
+        .collect();
+    let rows = rowset.rows.iter().map(|row| {
+        row.values()
+            .map(|value| match value {
+                Value::Null => nullvalue.to_owned(),
+                value => value.to_string(),
+            })
+            .collect::<Vec<_>>()
+    });
+
+    match mode {
+        Mode::Table => {
+            let mut table = comfy_table::Table::new();
+            table.set_header(header);
+            for row in rows {
+                table.add_row(row);
+            }
+            writeln!(out, "{}", table)
+        }
+        Mode::List => {
+            writeln!(out, "{}", header.join("|"))?;
+            for row in rows {
+                writeln!(out, "{}", row.join("|"))?;
+            }
+            Ok(())
+        }
+        Mode::Csv => {
+            write_csv_record(out, &header)?;
+            for row in rows {
+                write_csv_record(out, &row)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn write_csv_record(out: &mut dyn Write, fields: &[String]) -> io::Result<()> {
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+
+    write!(out, "{}\r\n", fields.join(","))
+}
@@ -0,0 +1,47 @@
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+const DEFAULT_PAGER: &str = "less -FRX";
+
+// Print |text| to stdout, piping it through $PAGER if it doesn't fit into terminal
+pub fn show(text: &str) {
+    if !fits_terminal(text) {
+        match page(text) {
+            Ok(()) => return,
+            Err(e) => eprintln!("Failed to run pager: {}", e),
+        }
+    }
+
+    println!("{}", text);
+}
+
+fn fits_terminal(text: &str) -> bool {
+    if !std::io::stdout().is_terminal() {
+        return true;
+    }
+
+    match terminal_size::terminal_size() {
+        // leave a line for the prompt
+        Some((_width, terminal_size::Height(height))) => text.lines().count() < height as usize,
+        None => true,
+    }
+}
+
+fn page(text: &str) -> std::io::Result<()> {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_owned());
+    let mut args = pager.split_whitespace();
+    let program = args.next().unwrap_or("less");
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // pager may exit before reading everything, e.g. on 'q', that's fine
+        let _ = writeln!(stdin, "{}", text);
+    }
+
+    child.wait()?;
+    Ok(())
+}
@@ -5,6 +5,9 @@ use sqlparser::ast;
 
 use crate::types::{type_of, BoxError, Result, Row};
 
+// Maximum number of columns in a table
+pub const MAX_COLUMNS: usize = 1024;
+
 // TODO: decouple type-level schema from table schema
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Schema {
@@ -12,6 +15,11 @@ pub struct Schema {
     // None => hidden primary key
     pub primary_key: Option<usize>,
     pub columns: Vec<Column>,
+    // Values must match column types exactly, otherwise they are converted on insert
+    // according to Type::convertable_to, e.g. 1 => true for bool column.
+    // Missing in schemas persisted before STRICT tables, see LegacySchema for bincode
+    #[serde(default)]
+    pub strict: bool,
 }
 
 impl Schema {
@@ -19,23 +27,47 @@ impl Schema {
         Schema {
             primary_key: None,
             columns: Vec::new(),
+            strict: false,
         }
     }
 
     pub fn new(desc: Vec<ast::ColumnDef>) -> Result<Self> {
-        let mut columns = Vec::with_capacity(desc.len());
+        if desc.is_empty() {
+            return Err("Table must have at least one column".into());
+        }
+
+        if desc.len() > MAX_COLUMNS {
+            return Err(format!(
+                "Too many columns: {}, at most {} are allowed",
+                desc.len(),
+                MAX_COLUMNS
+            )
+            .into());
+        }
+
+        let mut columns: Vec<Column> = Vec::with_capacity(desc.len());
         let mut primary_key = None;
         for (i, column) in desc.into_iter().enumerate() {
             if is_primary_key(&column) && primary_key.replace(i).is_some() {
                 return Err("At most one column of table must be marked as primary key".into());
             }
 
-            columns.push(Column::try_from(column)?);
+            let column = Column::try_from(column)?;
+            if column.name.is_empty() {
+                return Err("Column name cannot be empty".into());
+            }
+
+            if columns.iter().any(|c| c.name == column.name) {
+                return Err(format!("Duplicate column name: {}", column.name).into());
+            }
+
+            columns.push(column);
         }
 
         Ok(Schema {
             primary_key,
             columns,
+            strict: false,
         })
     }
 
@@ -43,6 +75,31 @@ impl Schema {
         self.columns.iter()
     }
 
+    // CREATE TABLE statement which produces table with this schema
+    pub fn to_create_table(&self, table: &str) -> String {
+        let columns: Vec<_> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let name = quote_ident(&column.name);
+                if self.primary_key == Some(i) {
+                    format!("{} {} PRIMARY KEY", name, column.type_)
+                } else {
+                    format!("{} {}", name, column.type_)
+                }
+            })
+            .collect();
+
+        let strict = if self.strict { " STRICT" } else { "" };
+        format!(
+            "CREATE TABLE {}({}){}",
+            quote_ident(table),
+            columns.join(", "),
+            strict
+        )
+    }
+
     pub fn check_compatible(&self, other: &Schema) -> Result<()> {
         if self.columns.len() != other.columns.len() {
             return Err(format!(
@@ -54,7 +111,10 @@ impl Schema {
         }
 
         for (this, other) in self.columns.iter().zip(other.columns()) {
-            if this.type_ != other.type_ {
+            let compatible = this.type_ == other.type_
+                || other.type_ == Type::Null
+                || (!self.strict && other.type_.convertable_to(this.type_));
+            if !compatible {
                 return Err(format!(
                     "Column {} type mismatch: expected {} but got {}",
                     this.name, this.type_, other.type_
@@ -88,6 +148,46 @@ impl Schema {
 
         Ok(())
     }
+
+    // Convert values of |row| to column types, unless schema is strict, and check the result
+    pub fn coerce(&self, row: Row) -> Result<Row> {
+        if self.strict || row.len() != self.columns.len() {
+            self.check(&row)?;
+            return Ok(row);
+        }
+
+        let mut values = Vec::with_capacity(row.len());
+        for (column, value) in self.columns.iter().zip(row.into_values()) {
+            let value_type = value.type_();
+            let value = value.convert(column.type_).ok_or_else(|| {
+                format!(
+                    "{} field type does not match: expected {} but got {}",
+                    column.name, column.type_, value_type
+                )
+            })?;
+            values.push(value);
+        }
+
+        Ok(Row::from(values))
+    }
+}
+
+// Schema as persisted before STRICT tables. bincode is not self-describing, so missing
+// trailing fields are not defaulted and old catalog entries are decoded with this layout
+#[derive(Deserialize)]
+pub(crate) struct LegacySchema {
+    primary_key: Option<usize>,
+    columns: Vec<Column>,
+}
+
+impl From<LegacySchema> for Schema {
+    fn from(schema: LegacySchema) -> Self {
+        Schema {
+            primary_key: schema.primary_key,
+            columns: schema.columns,
+            strict: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -101,7 +201,7 @@ impl TryFrom<ast::ColumnDef> for Column {
 
     fn try_from(column: ast::ColumnDef) -> Result<Self> {
         let type_ = type_of(&column)?;
-        let name = column.name.value;
+        let name = normalize_ident(&column.name);
         Ok(Column { name, type_ })
     }
 }
@@ -121,12 +221,23 @@ impl Type {
         }
 
         match self {
-            Type::Null => type_ == Type::Null,
+            // NULL is a valid value of any type
+            Type::Null => true,
             Type::Bool => type_ == Type::Integer,
             Type::Integer => type_ == Type::Bool,
             Type::Text => false,
         }
     }
+
+    // Type which values of both types convert to, e.g. for a column of VALUES
+    pub fn unify(self, other: Type) -> Option<Type> {
+        match (self, other) {
+            (a, b) if a == b => Some(a),
+            (Type::Null, t) | (t, Type::Null) => Some(t),
+            (Type::Bool, Type::Integer) | (Type::Integer, Type::Bool) => Some(Type::Integer),
+            _ => None,
+        }
+    }
 }
 
 impl Display for Type {
@@ -142,6 +253,28 @@ impl Display for Type {
     }
 }
 
+// Unquoted identifiers are case-insensitive and folded to lower case,
+// quoted ones ("Some Name") are kept as is
+pub fn normalize_ident(ident: &ast::Ident) -> String {
+    match ident.quote_style {
+        None => ident.value.to_lowercase(),
+        Some(_) => ident.value.clone(),
+    }
+}
+
+// Inverse of normalize_ident, quotes |name| only if it would change otherwise
+pub fn quote_ident(name: &str) -> String {
+    let plain = name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if plain {
+        name.to_owned()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
 fn is_primary_key(column: &ast::ColumnDef) -> bool {
     column.options.iter().any(|option| {
         matches!(
@@ -0,0 +1,79 @@
+use camellia::Dialect;
+use sqlparser::tokenizer::{Location, Token, Tokenizer, TokenizerError};
+
+// Single statement of a script
+pub struct Statement {
+    // 1-based line where statement starts
+    pub line: u64,
+    pub sql: String,
+}
+
+// Split |sql| into separate statements on semicolons outside of string literals and comments.
+// NOTE: the whole script is split with |dialect|, even if it changes the dialect with SET
+pub fn split(sql: &str, dialect: Dialect) -> Result<Vec<Statement>, TokenizerError> {
+    let tokens = Tokenizer::new(dialect.parser_dialect(), sql).tokenize_with_location()?;
+
+    let mut statements = Vec::new();
+    let mut start: Option<Location> = None;
+    for token in &tokens {
+        match token.token {
+            Token::Whitespace(_) => {}
+            Token::SemiColon => {
+                if let Some(start) = start.take() {
+                    let from = offset(sql, &start);
+                    let to = offset(sql, &token.location) + 1;
+                    statements.push(Statement {
+                        line: start.line,
+                        sql: sql[from..to].to_owned(),
+                    });
+                }
+            }
+            _ => {
+                if start.is_none() {
+                    start = Some(token.location.clone());
+                }
+            }
+        }
+    }
+
+    // last statement is allowed to omit the semicolon
+    if let Some(start) = start {
+        statements.push(Statement {
+            line: start.line,
+            sql: sql[offset(sql, &start)..].trim_end().to_owned(),
+        });
+    }
+
+    Ok(statements)
+}
+
+// Check whether |sql| ends with semicolon outside of string literal or comment
+pub fn is_complete(sql: &str, dialect: Dialect) -> bool {
+    match Tokenizer::new(dialect.parser_dialect(), sql).tokenize() {
+        Ok(tokens) => matches!(
+            tokens
+                .iter()
+                .rev()
+                .find(|token| !matches!(token, Token::Whitespace(_))),
+            Some(Token::SemiColon)
+        ),
+        // e.g. unterminated string literal, which may continue on the next line
+        Err(_) => false,
+    }
+}
+
+// Convert (line, column) location of tokenizer into byte offset in |sql|
+fn offset(sql: &str, location: &Location) -> usize {
+    let line_start: usize = sql
+        .split_inclusive('\n')
+        .take(location.line as usize - 1)
+        .map(str::len)
+        .sum();
+
+    let line = &sql[line_start..];
+    let column = line
+        .char_indices()
+        .nth(location.column as usize - 1)
+        .map_or(line.len(), |(i, _)| i);
+    line_start + column
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use sqlparser::ast;
+
+use crate::auth::{Authenticator, Permissions, StatementClass};
+use crate::dialect::Dialect;
+use crate::engine::{bind, Engine, Output, Stats};
+use crate::types::{Result, Value};
+
+// State of single client of network front-ends (http, grpc) and REPL
+pub struct Session {
+    engine: Arc<Engine>,
+    // None => anonymous, server runs without authentication
+    user: Option<String>,
+    permissions: Permissions,
+    // starts as Engine::dialect, changed with SET dialect = '...'
+    dialect: Dialect,
+    // false => session serves a single request, see Session::single_request
+    keeps_state: bool,
+}
+
+impl Session {
+    // Anonymous session which may run any statement
+    pub fn new(engine: Arc<Engine>) -> Self {
+        Session {
+            dialect: engine.dialect(),
+            engine,
+            user: None,
+            permissions: Permissions::ALL,
+            keeps_state: true,
+        }
+    }
+
+    // Session of |user| with permissions granted by |authenticator|
+    pub fn authenticate(
+        engine: Arc<Engine>,
+        authenticator: &dyn Authenticator,
+        user: &str,
+        password: &str,
+    ) -> Result<Self> {
+        let permissions = authenticator
+            .authenticate(user, password)
+            .ok_or("Invalid user name or password")?;
+        log::debug!(target: "camellia::auth", "authenticated {}: {:?}", user, permissions);
+        Ok(Session {
+            dialect: engine.dialect(),
+            engine,
+            user: Some(user.to_owned()),
+            permissions,
+            keeps_state: true,
+        })
+    }
+
+    // Session which is dropped after a single request, e.g. of http or grpc. SET dialect
+    // would not affect the following requests, so it is rejected
+    pub fn single_request(mut self) -> Self {
+        self.keeps_state = false;
+        self
+    }
+
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    pub fn permissions(&self) -> Permissions {
+        self.permissions
+    }
+
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    pub fn set_dialect(&mut self, dialect: Dialect) {
+        self.dialect = dialect;
+    }
+
+    // NOTE: blocks, should be called outside of async runtime threads
+    pub fn execute(&mut self, sql: &str, params: &[Value]) -> Result<Output> {
+        let mut stats = Stats::default();
+        self.run(sql, params, &mut stats)
+    }
+
+    // Same as execute, but also reports time spent in parse, plan and execute phases
+    pub fn execute_with_stats(&mut self, sql: &str) -> Result<(Output, Stats)> {
+        let mut stats = Stats::timed();
+        let output = self.run(sql, &[], &mut stats)?;
+        Ok((output, stats))
+    }
+
+    fn run(&mut self, sql: &str, params: &[Value], stats: &mut Stats) -> Result<Output> {
+        let mut program = self.engine.parse_with_dialect(sql, self.dialect)?;
+        stats.parse = stats.lap();
+        for statement in &program {
+            let class = StatementClass::of(statement);
+            if !self.permissions.allows(class) {
+                return Err(format!(
+                    "Permission denied: {} statements are not allowed for user {}",
+                    class,
+                    self.user.as_deref().unwrap_or("anonymous")
+                )
+                .into());
+            }
+        }
+
+        // SET dialect = 'postgres', applies to SQL of the following calls
+        if let [statement] = program.as_slice() {
+            if let Some(dialect) = dialect_of(statement)? {
+                if !self.keeps_state {
+                    return Err(
+                        "SET dialect is not supported, session ends with the request".into(),
+                    );
+                }
+                self.dialect = dialect;
+                log::debug!(target: "camellia::plan", "switched to {} dialect", dialect);
+                stats.execute = stats.lap();
+                return Ok(Output::Affected(0));
+            }
+        }
+
+        bind(&mut program, params)?;
+        self.engine.run_with_stats(program, stats)
+    }
+}
+
+// Dialect set by SET dialect statement, None for other statements
+fn dialect_of(statement: &ast::Statement) -> Result<Option<Dialect>> {
+    let value = match statement {
+        ast::Statement::SetVariable {
+            local: false,
+            hivevar: false,
+            variable,
+            value,
+        } if variable.to_string().eq_ignore_ascii_case("dialect") && value.len() == 1 => &value[0],
+        _ => return Ok(None),
+    };
+
+    let dialect: Dialect = match value {
+        ast::Expr::Value(ast::Value::SingleQuotedString(name)) => name.parse()?,
+        ast::Expr::Identifier(ident) => ident.value.parse()?,
+        e => return Err(format!("Expected dialect name, got {}", e).into()),
+    };
+    Ok(Some(dialect))
+}
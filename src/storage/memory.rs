@@ -0,0 +1,212 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+use super::{Direction, KeyValue, Storage, Transaction, CATALOG};
+use crate::types::Result;
+
+type Map = BTreeMap<Vec<u8>, Vec<u8>>;
+
+// Non-persistent storage, mostly useful for tests and targets without rocksdb (wasm)
+//
+// NOTE: transactions buffer their writes and apply them on commit,
+// concurrent transactions are not checked for conflicts
+pub struct Memory {
+    cfs: RwLock<HashMap<String, Map>>,
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        let mut cfs = HashMap::new();
+        cfs.insert(CATALOG.to_owned(), Map::new());
+        Memory {
+            cfs: RwLock::new(cfs),
+        }
+    }
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Storage for Memory {
+    fn create_cf(&self, name: &str) -> Result<()> {
+        let mut cfs = self.cfs.write().unwrap();
+        if cfs.contains_key(name) {
+            return Err(format!("Column family already exists: {}", name).into());
+        }
+
+        cfs.insert(name.to_owned(), Map::new());
+        Ok(())
+    }
+
+    fn drop_cf(&self, name: &str) -> Result<()> {
+        if name == CATALOG {
+            return Err("Cannot drop default column family".into());
+        }
+
+        self.cfs
+            .write()
+            .unwrap()
+            .remove(name)
+            .ok_or_else(|| format!("No such column family: {}", name))?;
+        Ok(())
+    }
+
+    fn has_cf(&self, name: &str) -> bool {
+        self.cfs.read().unwrap().contains_key(name)
+    }
+
+    fn properties(&self, name: &str) -> Result<Vec<(&'static str, u64)>> {
+        let cfs = self.cfs.read().unwrap();
+        let cf = cfs
+            .get(name)
+            .ok_or_else(|| format!("No such column family: {}", name))?;
+        // exact, only committed entries
+        Ok(vec![("estimated_keys", cf.len() as u64)])
+    }
+
+    fn transaction(&self) -> Box<dyn Transaction + '_> {
+        Box::new(MemoryTransaction {
+            storage: self,
+            writes: RefCell::new(BTreeMap::new()),
+        })
+    }
+}
+
+struct MemoryTransaction<'db> {
+    storage: &'db Memory,
+    // (cf, key) => Some(value) for put, None for delete
+    writes: RefCell<BTreeMap<(String, Vec<u8>), Option<Vec<u8>>>>,
+}
+
+impl<'db> MemoryTransaction<'db> {
+    fn check_cf(&self, cf: &str) -> Result<()> {
+        if !self.storage.has_cf(cf) {
+            return Err(format!("No such column family: {}", cf).into());
+        }
+
+        Ok(())
+    }
+}
+
+impl<'db> Transaction for MemoryTransaction<'db> {
+    fn get(&self, cf: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        if let Some(value) = self.writes.borrow().get(&(cf.to_owned(), key.to_vec())) {
+            return Ok(value.clone());
+        }
+
+        let cfs = self.storage.cfs.read().unwrap();
+        let map = cfs
+            .get(cf)
+            .ok_or_else(|| format!("No such column family: {}", cf))?;
+        Ok(map.get(key).cloned())
+    }
+
+    fn get_for_update(&self, cf: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.get(cf, key)
+    }
+
+    fn multi_get_for_update(&self, cf: &str, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>> {
+        let writes = self.writes.borrow();
+        let cfs = self.storage.cfs.read().unwrap();
+        let map = cfs
+            .get(cf)
+            .ok_or_else(|| format!("No such column family: {}", cf))?;
+
+        let mut write_key = (cf.to_owned(), Vec::new());
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            write_key.1.clear();
+            write_key.1.extend_from_slice(key);
+            let value = match writes.get(&write_key) {
+                Some(value) => value.clone(),
+                None => map.get(*key).cloned(),
+            };
+            values.push(value);
+        }
+
+        Ok(values)
+    }
+
+    fn put(&self, cf: &str, key: &[u8], value: &[u8]) -> Result<()> {
+        self.check_cf(cf)?;
+        self.writes
+            .borrow_mut()
+            .insert((cf.to_owned(), key.to_vec()), Some(value.to_vec()));
+        Ok(())
+    }
+
+    fn put_batch(&self, cf: &str, entries: &[KeyValue]) -> Result<()> {
+        self.check_cf(cf)?;
+        let mut writes = self.writes.borrow_mut();
+        for (key, value) in entries {
+            writes.insert((cf.to_owned(), key.to_vec()), Some(value.to_vec()));
+        }
+        Ok(())
+    }
+
+    fn delete(&self, cf: &str, key: &[u8]) -> Result<()> {
+        self.check_cf(cf)?;
+        self.writes
+            .borrow_mut()
+            .insert((cf.to_owned(), key.to_vec()), None);
+        Ok(())
+    }
+
+    fn iter(
+        &self,
+        cf: &str,
+        direction: Direction,
+    ) -> Result<Box<dyn Iterator<Item = Result<KeyValue>> + '_>> {
+        // TODO: merge committed entries with writes lazily instead of copying
+        let mut entries = {
+            let cfs = self.storage.cfs.read().unwrap();
+            cfs.get(cf)
+                .ok_or_else(|| format!("No such column family: {}", cf))?
+                .clone()
+        };
+
+        for ((write_cf, key), value) in self.writes.borrow().iter() {
+            if write_cf != cf {
+                continue;
+            }
+
+            match value {
+                Some(value) => entries.insert(key.clone(), value.clone()),
+                None => entries.remove(key),
+            };
+        }
+
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| -> Result<KeyValue> {
+                Ok((key.into_boxed_slice(), value.into_boxed_slice()))
+            });
+        match direction {
+            Direction::Forward => Ok(Box::new(entries)),
+            Direction::Reverse => Ok(Box::new(entries.rev())),
+        }
+    }
+
+    fn commit(self: Box<Self>) -> Result<()> {
+        let this = *self;
+        let writes = this.writes.into_inner();
+        let mut cfs = this.storage.cfs.write().unwrap();
+        if let Some((cf, _)) = writes.keys().find(|(cf, _)| !cfs.contains_key(cf)) {
+            return Err(format!("No such column family: {}", cf).into());
+        }
+
+        for ((cf, key), value) in writes {
+            let map = cfs.get_mut(&cf).unwrap();
+            match value {
+                Some(value) => map.insert(key, value),
+                None => map.remove(&key),
+            };
+        }
+
+        Ok(())
+    }
+}
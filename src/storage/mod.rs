@@ -0,0 +1,59 @@
+use crate::types::Result;
+
+mod memory;
+#[cfg(feature = "rocksdb")]
+mod rocks;
+
+pub use memory::Memory;
+#[cfg(feature = "rocksdb")]
+pub use rocks::RocksDb;
+
+// Column family which holds table schemas, always exists
+pub const CATALOG: &str = "default";
+
+pub type KeyValue = (Box<[u8]>, Box<[u8]>);
+
+pub enum Direction {
+    Forward,
+    Reverse,
+}
+
+// Key-value store with named column families, one per table
+pub trait Storage: Send + Sync {
+    fn create_cf(&self, name: &str) -> Result<()>;
+
+    fn drop_cf(&self, name: &str) -> Result<()>;
+
+    fn has_cf(&self, name: &str) -> bool;
+
+    // Backend specific statistics of column family, e.g. ("estimated_keys", 42)
+    fn properties(&self, name: &str) -> Result<Vec<(&'static str, u64)>>;
+
+    fn transaction(&self) -> Box<dyn Transaction + '_>;
+}
+
+pub trait Transaction {
+    fn get(&self, cf: &str, key: &[u8]) -> Result<Option<Vec<u8>>>;
+
+    // Same as get, but also locks the key until the end of transaction
+    fn get_for_update(&self, cf: &str, key: &[u8]) -> Result<Option<Vec<u8>>>;
+
+    // Same as get_for_update for each of |keys|, values are in the same order
+    fn multi_get_for_update(&self, cf: &str, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>>;
+
+    fn put(&self, cf: &str, key: &[u8], value: &[u8]) -> Result<()>;
+
+    // Same as put for each of |entries|, e.g. rows of bulk INSERT
+    fn put_batch(&self, cf: &str, entries: &[KeyValue]) -> Result<()>;
+
+    fn delete(&self, cf: &str, key: &[u8]) -> Result<()>;
+
+    // Iterate over all entries of column family in key order
+    fn iter(
+        &self,
+        cf: &str,
+        direction: Direction,
+    ) -> Result<Box<dyn Iterator<Item = Result<KeyValue>> + '_>>;
+
+    fn commit(self: Box<Self>) -> Result<()>;
+}
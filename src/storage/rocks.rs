@@ -0,0 +1,216 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use minitrace::local::LocalSpan;
+use rocksdb::{IteratorMode, Options, WriteBatchWithTransaction};
+
+use super::{Direction, KeyValue, Storage, Transaction, CATALOG};
+use crate::types::Result;
+
+type Database = rocksdb::TransactionDB<rocksdb::MultiThreaded>;
+type ColumnFamily<'db> = Arc<rocksdb::BoundColumnFamily<'db>>;
+
+// Reported by SHOW STATUS, (name, rocksdb property)
+const PROPERTIES: &[(&str, &str)] = &[
+    ("estimated_keys", "rocksdb.estimate-num-keys"),
+    ("sst_size", "rocksdb.total-sst-files-size"),
+    ("memtable_size", "rocksdb.cur-size-all-mem-tables"),
+];
+
+pub struct RocksDb {
+    db: Database,
+}
+
+impl RocksDb {
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let txn_db_opts = rocksdb::TransactionDBOptions::default();
+        let column_families = if path.exists() {
+            Database::list_cf(&opts, path)?
+        } else {
+            Vec::new()
+        };
+        log::info!(
+            target: "camellia::storage",
+            "opening {} with {} column family(ies)",
+            path.display(),
+            column_families.len()
+        );
+        let db = Database::open_cf(&opts, &txn_db_opts, path, column_families)?;
+        Ok(RocksDb { db })
+    }
+}
+
+impl Storage for RocksDb {
+    fn create_cf(&self, name: &str) -> Result<()> {
+        let opts = Options::default();
+        self.db.create_cf(name, &opts)?;
+        Ok(())
+    }
+
+    fn drop_cf(&self, name: &str) -> Result<()> {
+        self.db.drop_cf(name)?;
+        Ok(())
+    }
+
+    fn has_cf(&self, name: &str) -> bool {
+        name == CATALOG || self.db.cf_handle(name).is_some()
+    }
+
+    fn properties(&self, name: &str) -> Result<Vec<(&'static str, u64)>> {
+        let cf = match name {
+            CATALOG => None,
+            name => Some(
+                self.db
+                    .cf_handle(name)
+                    .ok_or_else(|| format!("No such column family: {}", name))?,
+            ),
+        };
+
+        let mut properties = Vec::with_capacity(PROPERTIES.len());
+        for (key, property) in PROPERTIES {
+            let value = match &cf {
+                Some(cf) => self.db.property_int_value_cf(cf, *property)?,
+                None => self.db.property_int_value(*property)?,
+            };
+
+            if let Some(value) = value {
+                properties.push((*key, value));
+            }
+        }
+
+        Ok(properties)
+    }
+
+    fn transaction(&self) -> Box<dyn Transaction + '_> {
+        Box::new(RocksTransaction {
+            db: &self.db,
+            txn: self.db.transaction(),
+        })
+    }
+}
+
+struct RocksTransaction<'db> {
+    db: &'db Database,
+    txn: rocksdb::Transaction<'db, Database>,
+}
+
+impl<'db> RocksTransaction<'db> {
+    // None => default column family (catalog)
+    fn cf(&self, name: &str) -> Result<Option<ColumnFamily<'db>>> {
+        if name == CATALOG {
+            return Ok(None);
+        }
+
+        let cf = self
+            .db
+            .cf_handle(name)
+            .ok_or_else(|| format!("No such column family: {}", name))?;
+        Ok(Some(cf))
+    }
+}
+
+impl<'db> Transaction for RocksTransaction<'db> {
+    fn get(&self, cf: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let span = LocalSpan::enter_with_local_parent("rocksdb::get");
+        let value = match self.cf(cf)? {
+            Some(cf) => self.txn.get_cf(&cf, key)?,
+            None => self.txn.get(key)?,
+        };
+        let _span = span.with_property(|| ("bytes", value_len(&value).to_string()));
+        Ok(value)
+    }
+
+    fn get_for_update(&self, cf: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let span = LocalSpan::enter_with_local_parent("rocksdb::get_for_update");
+        let value = match self.cf(cf)? {
+            Some(cf) => self.txn.get_for_update_cf(&cf, key, true)?,
+            None => self.txn.get_for_update(key, true)?,
+        };
+        let _span = span.with_property(|| ("bytes", value_len(&value).to_string()));
+        Ok(value)
+    }
+
+    fn multi_get_for_update(&self, cf: &str, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>> {
+        // NOTE: rocksdb has no MultiGetForUpdate and MultiGet doesn't lock keys, but
+        // column family is resolved and span is entered once per batch
+        let span = LocalSpan::enter_with_local_parent("rocksdb::multi_get_for_update");
+        let cf = self.cf(cf)?;
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            let value = match &cf {
+                Some(cf) => self.txn.get_for_update_cf(cf, key, true)?,
+                None => self.txn.get_for_update(key, true)?,
+            };
+            values.push(value);
+        }
+        let _span = span.with_property(|| ("keys", keys.len().to_string()));
+        Ok(values)
+    }
+
+    fn put(&self, cf: &str, key: &[u8], value: &[u8]) -> Result<()> {
+        let _span = LocalSpan::enter_with_local_parent("rocksdb::put")
+            .with_property(|| ("bytes", (key.len() + value.len()).to_string()));
+        match self.cf(cf)? {
+            Some(cf) => self.txn.put_cf(&cf, key, value)?,
+            None => self.txn.put(key, value)?,
+        };
+        Ok(())
+    }
+
+    fn put_batch(&self, cf: &str, entries: &[KeyValue]) -> Result<()> {
+        let _span = LocalSpan::enter_with_local_parent("rocksdb::put_batch")
+            .with_property(|| ("entries", entries.len().to_string()));
+        let mut batch = WriteBatchWithTransaction::<true>::default();
+        let cf = self.cf(cf)?;
+        for (key, value) in entries {
+            match &cf {
+                Some(cf) => batch.put_cf(cf, key, value),
+                None => batch.put(key, value),
+            }
+        }
+        // batch is applied to the transaction in one call and written together with the
+        // rest of it on commit, keys are already locked by multi_get_for_update
+        self.txn.rebuild_from_writebatch(&batch)?;
+        Ok(())
+    }
+
+    fn delete(&self, cf: &str, key: &[u8]) -> Result<()> {
+        let _span = LocalSpan::enter_with_local_parent("rocksdb::delete");
+        match self.cf(cf)? {
+            Some(cf) => self.txn.delete_cf(&cf, key)?,
+            None => self.txn.delete(key)?,
+        };
+        Ok(())
+    }
+
+    fn iter(
+        &self,
+        cf: &str,
+        direction: Direction,
+    ) -> Result<Box<dyn Iterator<Item = Result<KeyValue>> + '_>> {
+        let mode = match direction {
+            Direction::Forward => IteratorMode::Start,
+            Direction::Reverse => IteratorMode::End,
+        };
+
+        let iter = match self.cf(cf)? {
+            Some(cf) => self.txn.iterator_cf(&cf, mode),
+            None => self.txn.iterator(mode),
+        };
+
+        Ok(Box::new(iter.map(|item| -> Result<KeyValue> { Ok(item?) })))
+    }
+
+    fn commit(self: Box<Self>) -> Result<()> {
+        let _span = LocalSpan::enter_with_local_parent("rocksdb::commit");
+        let this = *self;
+        this.txn.commit()?;
+        Ok(())
+    }
+}
+
+fn value_len(value: &Option<Vec<u8>>) -> usize {
+    value.as_ref().map_or(0, Vec::len)
+}
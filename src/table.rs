@@ -1,36 +1,79 @@
 use std::sync::atomic::{AtomicU64, Ordering};
 
 use crate::schema::Schema;
-use crate::types::{Row, Value};
+use crate::types::{Result, Row, Value};
+
+// Row count is persisted after this many changes, see Table::add_rows
+const PERSIST_ROWS_EVERY: u64 = 1024;
 
 #[derive(Debug)]
 pub struct Table {
     schema: Schema,
+    // generation of catalog entry this table is loaded from
+    generation: u64,
 
     hidden_pk: AtomicU64,
+    // approximate, may lag behind after crash. Reported by SHOW STATUS and
+    // EXPLAIN of full scans
+    rows: AtomicU64,
+    // rows added since row count was last persisted
+    unpersisted: AtomicU64,
 }
 
 impl Table {
-    pub fn new(schema: Schema, hidden_pk: u64) -> Self {
+    pub fn new(schema: Schema, generation: u64, hidden_pk: u64, rows: u64) -> Self {
         Table {
             schema,
+            generation,
             hidden_pk: AtomicU64::new(hidden_pk),
+            rows: AtomicU64::new(rows),
+            unpersisted: AtomicU64::new(0),
         }
     }
 
-    pub fn get_key(&self, row: &Row, key: &mut Vec<u8>) {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn estimated_rows(&self) -> u64 {
+        self.rows.load(Ordering::Relaxed)
+    }
+
+    // Account |n| committed rows, returns true if row count should be persisted
+    pub fn add_rows(&self, n: u64) -> bool {
+        self.rows.fetch_add(n, Ordering::Relaxed);
+        self.unpersisted.fetch_add(n, Ordering::Relaxed) + n >= PERSIST_ROWS_EVERY
+    }
+
+    pub fn has_unpersisted_rows(&self) -> bool {
+        self.unpersisted.load(Ordering::Relaxed) != 0
+    }
+
+    // Returns row count to persist
+    pub fn mark_persisted(&self) -> u64 {
+        self.unpersisted.store(0, Ordering::Relaxed);
+        self.rows.load(Ordering::Relaxed)
+    }
+
+    // Append storage key of |row| to |key|, PRIMARY KEY implies NOT NULL
+    pub fn get_key(&self, row: &Row, key: &mut Vec<u8>) -> Result<()> {
         match self.schema.primary_key {
             None => {
                 let bytes = self.hidden_pk.fetch_add(1, Ordering::Relaxed).to_be_bytes();
                 key.extend_from_slice(&bytes);
             }
             Some(index) => match row.get(index) {
-                Value::Null => { /* leave key empty */ },
+                Value::Null => {
+                    let column = &self.schema.columns[index].name;
+                    return Err(format!("NULL value in primary key column {}", column).into());
+                }
                 Value::Bool(val) => key.push(*val as u8),
                 Value::Int(val) => key.extend_from_slice(&val.to_be_bytes()),
                 Value::String(val) => key.extend_from_slice(val.as_bytes()),
             },
         };
+
+        Ok(())
     }
 
     pub fn schema(&self) -> &Schema {
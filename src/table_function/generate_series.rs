@@ -0,0 +1,63 @@
+use super::{Batches, TableFunction};
+use crate::schema::{Column, Schema, Type};
+use crate::types::{Result, Row, Value};
+
+const BATCH_SIZE: usize = 1024;
+
+// generate_series(start, stop[, step]): INT column "value" with start, start + step, ...
+// up to stop inclusive, counting down for negative step. NULL argument gives no rows
+pub struct GenerateSeries;
+
+impl TableFunction for GenerateSeries {
+    fn call(&self, args: &[Value]) -> Result<(Schema, Batches)> {
+        if !(2..=3).contains(&args.len()) {
+            return Err("Expected generate_series(start, stop[, step])".into());
+        }
+
+        let schema = Schema {
+            primary_key: None,
+            columns: vec![Column {
+                name: "value".to_owned(),
+                type_: Type::Integer,
+            }],
+            strict: false,
+        };
+        if args.iter().any(Value::is_null) {
+            return Ok((schema, Box::new(std::iter::empty())));
+        }
+
+        let int = |value: &Value| {
+            value
+                .to_int()
+                .ok_or("generate_series arguments must be INT")
+        };
+        let start = int(&args[0])?;
+        let stop = int(&args[1])?;
+        let step = match args.get(2) {
+            Some(step) => int(step)?,
+            None => 1,
+        };
+        if step == 0 {
+            return Err("generate_series step cannot be 0".into());
+        }
+
+        let mut next = Some(start);
+        let batches = std::iter::from_fn(move || {
+            let mut batch = Vec::new();
+            while batch.len() < BATCH_SIZE {
+                let value = match next {
+                    Some(value) if (step > 0 && value <= stop) || (step < 0 && value >= stop) => {
+                        value
+                    }
+                    _ => break,
+                };
+                batch.push(Row::from(vec![Value::Int(value)]));
+                // series ends on overflow, e.g. generate_series(1, 9223372036854775807)
+                next = value.checked_add(step);
+            }
+            (!batch.is_empty()).then_some(Ok(batch))
+        });
+
+        Ok((schema, Box::new(batches)))
+    }
+}
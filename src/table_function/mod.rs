@@ -0,0 +1,40 @@
+// Rust-defined sources of rows, queried as SELECT * FROM numbers(10):
+//
+//  struct Numbers;
+//
+//  impl TableFunction for Numbers {
+//      fn call(&self, args: &[Value]) -> Result<(Schema, Batches)> {
+//          let n = args.first().and_then(Value::to_int).ok_or("Expected number of rows")?;
+//          let rows = (0..n).map(|i| Row::from(vec![Value::Int(i)])).collect();
+//          Ok((numbers_schema(), Box::new(std::iter::once(Ok(rows)))))
+//      }
+//  }
+//
+//  let engine = Engine::builder().table_function("numbers", Arc::new(Numbers)).in_memory();
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::schema::Schema;
+use crate::types::{Result, Row, Value};
+
+mod generate_series;
+mod read_csv;
+
+pub use generate_series::GenerateSeries;
+pub use read_csv::ReadCsv;
+
+// Rows of the result, pulled batch by batch while the query is executed
+pub type Batches = Box<dyn Iterator<Item = Result<Vec<Row>>>>;
+
+pub trait TableFunction: Send + Sync {
+    // Called while planning the query, |args| are constant expressions of the call.
+    // Rows should be produced lazily, e.g. large files are not read into memory
+    fn call(&self, args: &[Value]) -> Result<(Schema, Batches)>;
+}
+
+// Functions available in every engine, may be replaced with EngineBuilder::table_function
+pub(crate) fn builtins() -> HashMap<String, Arc<dyn TableFunction>> {
+    let mut functions: HashMap<String, Arc<dyn TableFunction>> = HashMap::new();
+    functions.insert("generate_series".to_owned(), Arc::new(GenerateSeries));
+    functions
+}
@@ -0,0 +1,232 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use sqlparser::ast;
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+
+use super::{Batches, TableFunction};
+use crate::schema::{Schema, Type};
+use crate::types::{BoxError, Result, Row, Value};
+
+// Rows read to infer column types
+const INFER_ROWS: usize = 100;
+const BATCH_SIZE: usize = 1024;
+
+// read_csv('path') or read_csv('path', 'a INT, b TEXT'): rows of RFC 4180 file with header.
+// Without explicit columns their names are taken from the header and types are inferred
+// from the first INFER_ROWS rows: INT or BOOL if all values parse as such, TEXT otherwise.
+// Empty unquoted fields are NULL.
+//
+// NOTE: not registered by default, it gives SQL access to files of the host
+pub struct ReadCsv;
+
+impl TableFunction for ReadCsv {
+    fn call(&self, args: &[Value]) -> Result<(Schema, Batches)> {
+        let (path, columns) = match args {
+            [Value::String(path)] => (path, None),
+            [Value::String(path), Value::String(columns)] => (path, Some(columns)),
+            _ => return Err("Expected read_csv('path'[, 'column type, ...'])".into()),
+        };
+
+        let file = File::open(path).map_err(|e| format!("read_csv: {}: {}", path, e))?;
+        let mut reader = Reader {
+            input: BufReader::new(file),
+            line: 0,
+        };
+        let (_, header) = reader
+            .record()?
+            .ok_or_else(|| format!("read_csv: {} is empty", path))?;
+
+        // rows read for inference are returned first
+        let mut sample = Vec::new();
+        let schema = match columns {
+            Some(columns) => parse_columns(columns)?,
+            None => {
+                while sample.len() < INFER_ROWS {
+                    match reader.record()? {
+                        Some(record) => sample.push(record),
+                        None => break,
+                    }
+                }
+                infer_schema(header, &sample).map_err(|e| format!("read_csv: {}: {}", path, e))?
+            }
+        };
+
+        let types: Vec<Type> = schema.columns().map(|column| column.type_).collect();
+        let mut records = sample
+            .into_iter()
+            .map(Ok)
+            .chain(std::iter::from_fn(move || reader.record().transpose()));
+        let batches = std::iter::from_fn(move || {
+            let mut batch = Vec::new();
+            for record in records.by_ref().take(BATCH_SIZE) {
+                match record.and_then(|(line, fields)| convert(line, fields, &types)) {
+                    Ok(row) => batch.push(row),
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            (!batch.is_empty()).then_some(Ok(batch))
+        });
+
+        Ok((schema, Box::new(batches)))
+    }
+}
+
+// Fields of a record, None for empty unquoted field
+type Fields = Vec<Option<String>>;
+
+struct Reader {
+    input: BufReader<File>,
+    // number of the last read line, 1-based
+    line: usize,
+}
+
+impl Reader {
+    // Next record with the line it starts at, quoted fields may span several lines
+    fn record(&mut self) -> Result<Option<(usize, Fields)>> {
+        let mut buf = String::new();
+        // blank lines are skipped
+        while buf.trim_end_matches(['\r', '\n']).is_empty() {
+            buf.clear();
+            if self.input.read_line(&mut buf)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+        }
+
+        let start = self.line;
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut in_quotes = false;
+        loop {
+            let mut chars = buf.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' if in_quotes && chars.peek() == Some(&'"') => {
+                        field.push('"');
+                        chars.next();
+                    }
+                    '"' if in_quotes => in_quotes = false,
+                    '"' if field.is_empty() && !quoted => {
+                        quoted = true;
+                        in_quotes = true;
+                    }
+                    ',' if !in_quotes => {
+                        let field = std::mem::take(&mut field);
+                        fields.push((quoted || !field.is_empty()).then_some(field));
+                        quoted = false;
+                    }
+                    '\r' | '\n' if !in_quotes => {}
+                    c => field.push(c),
+                }
+            }
+
+            if !in_quotes {
+                break;
+            }
+
+            buf.clear();
+            if self.input.read_line(&mut buf)? == 0 {
+                return Err(format!("read_csv: line {}: unterminated quoted field", start).into());
+            }
+            self.line += 1;
+        }
+
+        fields.push((quoted || !field.is_empty()).then_some(field));
+        Ok(Some((start, fields)))
+    }
+}
+
+// 'a INT, b TEXT' => schema with columns a and b
+fn parse_columns(columns: &str) -> Result<Schema> {
+    let sql = format!("CREATE TABLE t ({})", columns);
+    let program = Parser::parse_sql(&GenericDialect {}, &sql)?;
+    match program.into_iter().next() {
+        Some(ast::Statement::CreateTable { columns, .. }) => Schema::new(columns),
+        _ => Err(format!("read_csv: invalid columns: {}", columns).into()),
+    }
+}
+
+// Names of the header are treated as unquoted identifiers, e.g. Name => name,
+// and validated same as columns of CREATE TABLE
+fn infer_schema(header: Fields, sample: &[(usize, Fields)]) -> Result<Schema> {
+    let columns = header
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let values = || {
+                sample
+                    .iter()
+                    .filter_map(move |(_, fields)| fields.get(i).cloned().flatten())
+            };
+            let data_type = if values().next().is_none() {
+                ast::DataType::Text
+            } else if values().all(|value| value.parse::<i64>().is_ok()) {
+                ast::DataType::Int(None)
+            } else if values().all(|value| parse_bool(&value).is_some()) {
+                ast::DataType::Boolean
+            } else {
+                ast::DataType::Text
+            };
+
+            let name = name.unwrap_or_else(|| format!("column{}", i + 1));
+            ast::ColumnDef {
+                name: ast::Ident::new(name),
+                data_type,
+                collation: None,
+                options: Vec::new(),
+            }
+        })
+        .collect();
+
+    Schema::new(columns)
+}
+
+fn convert(line: usize, fields: Fields, types: &[Type]) -> Result<Row> {
+    if fields.len() != types.len() {
+        return Err(format!(
+            "read_csv: line {}: expected {} field(s), got {}",
+            line,
+            types.len(),
+            fields.len()
+        )
+        .into());
+    }
+
+    let mut values = Vec::with_capacity(fields.len());
+    for (field, type_) in fields.into_iter().zip(types) {
+        let value = match (field, type_) {
+            (None, _) => Value::Null,
+            (Some(field), Type::Integer) => match field.parse() {
+                Ok(val) => Value::Int(val),
+                Err(_) => return Err(invalid(line, &field, *type_)),
+            },
+            (Some(field), Type::Bool) => match parse_bool(&field) {
+                Some(val) => Value::Bool(val),
+                None => return Err(invalid(line, &field, *type_)),
+            },
+            (Some(field), _) => Value::String(field),
+        };
+        values.push(value);
+    }
+
+    Ok(Row::from(values))
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+fn invalid(line: usize, field: &str, type_: Type) -> BoxError {
+    format!(
+        "read_csv: line {}: invalid {} value '{}'",
+        line, type_, field
+    )
+    .into()
+}
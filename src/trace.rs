@@ -1,23 +1,56 @@
+// Export of minitrace spans recorded by the engine.
+//
+// Every statement run by Engine gets a root "query" span, |sample_ratio| of them are reported:
+//
+//  camellia::trace::set_reporter(ConsoleReporter, 0.1);
+//  ...
+//  camellia::trace::shutdown();
+//
+// NOTE: spans are only collected with "trace" feature
+#[cfg(feature = "otlp")]
 use std::borrow::Cow;
-use std::error::Error;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+#[cfg(feature = "otlp")]
 use std::time::Duration;
 
-use minitrace::collector::Config as TracingConfig;
+use minitrace::collector::{Config as TracingConfig, Reporter, SpanContext};
+use minitrace::Span;
+#[cfg(feature = "otlp")]
 use minitrace_opentelemetry::OpenTelemetryReporter;
+#[cfg(feature = "otlp")]
 use opentelemetry::trace::SpanKind;
+#[cfg(feature = "otlp")]
 use opentelemetry::{InstrumentationLibrary, KeyValue};
+#[cfg(feature = "otlp")]
 use opentelemetry_otlp::WithExportConfig;
+#[cfg(feature = "otlp")]
 use opentelemetry_sdk::Resource;
 
+use crate::types::Result;
+
 static ENABLED: AtomicBool = AtomicBool::new(false);
+// f64 bits of fraction of statements which are traced
+static SAMPLE_RATIO: AtomicU64 = AtomicU64::new(0);
+
+// Report spans of sampled statements to |reporter|, |sample_ratio| is in [0, 1]
+pub fn set_reporter(reporter: impl Reporter, sample_ratio: f64) -> Result<()> {
+    if !(0.0..=1.0).contains(&sample_ratio) {
+        return Err(format!("Invalid sample ratio: {}", sample_ratio).into());
+    }
 
-pub fn init(endpoint: String) -> Result<(), Box<dyn Error>> {
     if ENABLED.swap(true, Ordering::SeqCst) {
         return Err("Tracing already initialized".into());
     }
 
-    let config = TracingConfig::default();
+    SAMPLE_RATIO.store(sample_ratio.to_bits(), Ordering::Relaxed);
+    minitrace::set_reporter(reporter, TracingConfig::default());
+    Ok(())
+}
+
+// Export spans to OpenTelemetry collector at |endpoint| over grpc, e.g. http://localhost:4317
+// NOTE: must be called within tokio runtime
+#[cfg(feature = "otlp")]
+pub fn init_otlp(endpoint: String, sample_ratio: f64) -> Result<()> {
     let opentelemetry_exporter = opentelemetry_otlp::new_exporter()
         .tonic()
         .with_endpoint(endpoint)
@@ -25,8 +58,7 @@ pub fn init(endpoint: String) -> Result<(), Box<dyn Error>> {
         .with_timeout(Duration::from_secs(
             opentelemetry_otlp::OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT,
         ))
-        .build_span_exporter()
-        .expect("initialize oltp exporter");
+        .build_span_exporter()?;
 
     let resource = Resource::new([KeyValue::new("service.name", "camellia")]);
     let resource = Cow::Owned(resource);
@@ -40,10 +72,10 @@ pub fn init(endpoint: String) -> Result<(), Box<dyn Error>> {
         instrumentation_lib,
     );
 
-    minitrace::set_reporter(reporter, config);
-    Ok(())
+    set_reporter(reporter, sample_ratio)
 }
 
+// Flush spans which are not reported yet
 pub fn shutdown() {
     if !ENABLED.swap(false, Ordering::SeqCst) {
         return;
@@ -51,3 +83,19 @@ pub fn shutdown() {
 
     minitrace::flush();
 }
+
+// Root span of statement, noop if tracing is disabled or statement is not sampled
+pub(crate) fn root<F: FnOnce() -> String>(name: &'static str, sql: F) -> Span {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return Span::noop();
+    }
+
+    // trace id is random anyway, no need for separate rng
+    let context = SpanContext::random();
+    let ratio = f64::from_bits(SAMPLE_RATIO.load(Ordering::Relaxed));
+    if (context.trace_id.0 as u64) as f64 >= ratio * u64::MAX as f64 {
+        return Span::noop();
+    }
+
+    Span::root(name, context).with_property(|| ("query", sql()))
+}
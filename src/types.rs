@@ -3,11 +3,11 @@ use std::fmt::Display;
 use serde::{Deserialize, Serialize};
 use sqlparser::ast::{self, ColumnDef};
 
+use crate::error::Error;
 use crate::schema::{Schema, Type};
 
 pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
 pub type Result<T> = std::result::Result<T, BoxError>;
-pub type Database = rocksdb::TransactionDB<rocksdb::MultiThreaded>;
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 pub struct Row(Vec<Value>);
@@ -31,6 +31,10 @@ impl Row {
         self.0.iter()
     }
 
+    pub fn into_values(self) -> impl Iterator<Item = Value> {
+        self.0.into_iter()
+    }
+
     pub fn get(&self, i: usize) -> &Value {
         &self.0[i]
     }
@@ -80,14 +84,33 @@ impl Value {
         let value = match value {
             ast::Value::Null => Value::Null,
             ast::Value::Boolean(val) => Value::Bool(val),
-            ast::Value::Number(number, false) => Value::Int(number.parse::<i64>()?),
-            ast::Value::SingleQuotedString(string) => Value::String(string),
+            // "long" flag is set for MySQL style 123L, it's INT anyway
+            ast::Value::Number(number, _) => Value::Int(parse_int(&number)?),
+            // 0xFF is tokenized as hex string, X'FF' blobs are rejected by Engine::parse
+            ast::Value::HexStringLiteral(hex) => Value::Int(parse_hex(&hex)?),
+            // quotes ('it''s') and backslash escapes (E'it\'s') are already unescaped by tokenizer
+            ast::Value::SingleQuotedString(string)
+            | ast::Value::EscapedStringLiteral(string)
+            | ast::Value::NationalStringLiteral(string) => Value::String(string),
             _ => return Err("Unsupported value type".into()),
         };
 
         Ok(value)
     }
 
+    pub fn to_ast(&self) -> ast::Value {
+        match self {
+            Value::Null => ast::Value::Null,
+            Value::Bool(val) => ast::Value::Boolean(*val),
+            Value::Int(val) => ast::Value::Number(val.to_string(), false),
+            // E'...' keeps control characters printable, e.g. in EXPLAIN output
+            Value::String(val) if val.contains(['\n', '\r', '\t']) => {
+                ast::Value::EscapedStringLiteral(val.clone())
+            }
+            Value::String(val) => ast::Value::SingleQuotedString(val.clone()),
+        }
+    }
+
     pub fn type_(&self) -> Type {
         match self {
             Value::Null => Type::Null,
@@ -97,44 +120,135 @@ impl Value {
         }
     }
 
+    // Convert to |type_| if Type::convertable_to allows it, NULL stays NULL
+    pub fn convert(self, type_: Type) -> Option<Value> {
+        match (self, type_) {
+            (Value::Null, _) => Some(Value::Null),
+            (Value::Int(val), Type::Bool) => Some(Value::Bool(val != 0)),
+            (Value::Bool(val), Type::Integer) => Some(Value::Int(val as i64)),
+            (value, type_) if value.type_() == type_ => Some(value),
+            _ => None,
+        }
+    }
+
     pub fn add(&self, right: Value) -> Result<Value> {
+        if self.is_null() || right.is_null() {
+            return Ok(Value::Null);
+        }
         let left = self.to_int().ok_or("Invalid ADD")?;
         let right = right.to_int().ok_or("Invalid ADD")?;
-        let result = left.checked_add(right).ok_or("Integer overflow on ADD")?;
+        let result = left.checked_add(right).ok_or(Error::Overflow("ADD"))?;
         Ok(Value::Int(result))
     }
 
     pub fn sub(&self, right: Value) -> Result<Value> {
+        if self.is_null() || right.is_null() {
+            return Ok(Value::Null);
+        }
         let left = self.to_int().ok_or("Invalid SUB")?;
         let right = right.to_int().ok_or("Invalid SUB")?;
-        let result = left.checked_sub(right).ok_or("Integer overflow on SUB")?;
+        let result = left.checked_sub(right).ok_or(Error::Overflow("SUB"))?;
         Ok(Value::Int(result))
     }
 
     pub fn mul(&self, right: Value) -> Result<Value> {
+        if self.is_null() || right.is_null() {
+            return Ok(Value::Null);
+        }
         let left = self.to_int().ok_or("Invalid MUL")?;
         let right = right.to_int().ok_or("Invalid MUL")?;
-        let result = left.checked_mul(right).ok_or("Integer overflow on MUL")?;
+        let result = left.checked_mul(right).ok_or(Error::Overflow("MUL"))?;
         Ok(Value::Int(result))
     }
 
     pub fn div(&self, right: Value) -> Result<Value> {
+        if self.is_null() || right.is_null() {
+            return Ok(Value::Null);
+        }
         let left = self.to_int().ok_or("Invalid DIV")?;
         let right = right.to_int().ok_or("Invalid DIV")?;
-        let result = left.checked_div(right).ok_or("Integer overflow on DIV")?;
+        if right == 0 {
+            return Err(Error::DivisionByZero.into());
+        }
+
+        // i64::MIN / -1
+        let result = left.checked_div(right).ok_or(Error::Overflow("DIV"))?;
+        Ok(Value::Int(result))
+    }
+
+    pub fn rem(&self, right: Value) -> Result<Value> {
+        if self.is_null() || right.is_null() {
+            return Ok(Value::Null);
+        }
+        let left = self.to_int().ok_or("Invalid MOD")?;
+        let right = right.to_int().ok_or("Invalid MOD")?;
+        if right == 0 {
+            return Err(Error::DivisionByZero.into());
+        }
+
+        // unlike division, i64::MIN % -1 is well defined and equals 0
+        Ok(Value::Int(left.wrapping_rem(right)))
+    }
+
+    pub fn neg(&self) -> Result<Value> {
+        if self.is_null() {
+            return Ok(Value::Null);
+        }
+        let val = self.to_int().ok_or("Cannot convert to INT for unary '-'")?;
+        let result = val.checked_neg().ok_or(Error::Overflow("NEG"))?;
         Ok(Value::Int(result))
     }
 
+    pub fn abs(&self) -> Result<Value> {
+        if self.is_null() {
+            return Ok(Value::Null);
+        }
+        let val = self.to_int().ok_or("Cannot convert 'abs' arg to integer")?;
+        let result = val.checked_abs().ok_or(Error::Overflow("ABS"))?;
+        Ok(Value::Int(result))
+    }
+
+    // Three-valued logic: false AND NULL is false, true AND NULL is NULL
     pub fn and(&self, right: Value) -> Result<Value> {
-        let left = self.to_bool().ok_or("Invalid AND")?;
-        let right = right.to_bool().ok_or("Invalid AND")?;
-        Ok(Value::Bool(left && right))
+        let left = self.to_truth().ok_or("Invalid AND")?;
+        let right = right.to_truth().ok_or("Invalid AND")?;
+        let result = match (left, right) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        };
+        Ok(result.map_or(Value::Null, Value::Bool))
     }
 
+    // Three-valued logic: true OR NULL is true, false OR NULL is NULL
     pub fn or(&self, right: Value) -> Result<Value> {
-        let left = self.to_bool().ok_or("Invalid OR")?;
-        let right = right.to_bool().ok_or("Invalid OR")?;
-        Ok(Value::Bool(left || right))
+        let left = self.to_truth().ok_or("Invalid OR")?;
+        let right = right.to_truth().ok_or("Invalid OR")?;
+        let result = match (left, right) {
+            (Some(true), _) | (_, Some(true)) => Some(true),
+            (Some(false), Some(false)) => Some(false),
+            _ => None,
+        };
+        Ok(result.map_or(Value::Null, Value::Bool))
+    }
+
+    pub fn not(&self) -> Result<Value> {
+        let val = self
+            .to_truth()
+            .ok_or("Cannot convert to BOOL for NOT operation")?;
+        Ok(val.map_or(Value::Null, |val| Value::Bool(!val)))
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    // Truth value, None is unknown, i.e. NULL
+    pub fn to_truth(&self) -> Option<Option<bool>> {
+        match self {
+            Value::Null => Some(None),
+            val => val.to_bool().map(Some),
+        }
     }
 
     pub fn to_bool(&self) -> Option<bool> {
@@ -154,6 +268,42 @@ impl Value {
     }
 }
 
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Int(value.into())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_owned())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -165,6 +315,27 @@ impl Display for Value {
     }
 }
 
+// Decimal literal, |number| may start with '-' if negation was folded into it
+pub fn parse_int(number: &str) -> Result<i64> {
+    let digits = number.strip_prefix('-').unwrap_or(number);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("Unsupported numeric literal: {}", number).into());
+    }
+
+    number
+        .parse::<i64>()
+        .map_err(|_| Error::OutOfRange(number.to_owned()).into())
+}
+
+// Hex literal without 0x prefix, must fit into INT, i.e. 0x8000000000000000 is out of range
+fn parse_hex(hex: &str) -> Result<i64> {
+    if hex.is_empty() || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("Unsupported hex literal: 0x{}", hex).into());
+    }
+
+    i64::from_str_radix(hex, 16).map_err(|_| Error::OutOfRange(format!("0x{}", hex)).into())
+}
+
 pub fn type_of(column: &ColumnDef) -> Result<Type> {
     match column.data_type {
         ast::DataType::Bool | ast::DataType::Boolean => Ok(Type::Bool),
@@ -0,0 +1,191 @@
+// Access hooks of embedders, see camellia::AccessHook
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use camellia::{Access, AccessHook, Engine, Output, Value};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+// Rows of orders are visible and insertable only for the current tenant.
+// Reading or dropping secret and creating forbidden are denied
+struct Tenant(Arc<AtomicI64>);
+
+impl AccessHook for Tenant {
+    fn check(&self, table: &str, access: Access) -> Result<()> {
+        match (table, access) {
+            ("secret", Access::Read | Access::Drop) | ("forbidden", Access::Create) => {
+                Err(format!("{:?} access to {} denied", access, table).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn predicate(&self, table: &str, _access: Access) -> Option<String> {
+        (table == "orders").then(|| format!("tenant = {}", self.0.load(Ordering::Relaxed)))
+    }
+}
+
+// Same predicate for reading orders, whatever the tenant is
+struct ReadPredicate(&'static str);
+
+impl AccessHook for ReadPredicate {
+    fn predicate(&self, table: &str, access: Access) -> Option<String> {
+        (table == "orders" && access == Access::Read).then(|| self.0.to_owned())
+    }
+}
+
+fn setup(hooks: Vec<Arc<dyn AccessHook>>) -> (Engine, Arc<AtomicI64>) {
+    let tenant = Arc::new(AtomicI64::new(1));
+    let mut builder = Engine::builder().access_hook(Arc::new(Tenant(tenant.clone())));
+    for hook in hooks {
+        builder = builder.access_hook(hook);
+    }
+    let engine = builder.in_memory();
+    engine
+        .run_sql("create table orders(id int primary key, tenant int, amount int)")
+        .unwrap();
+    (engine, tenant)
+}
+
+fn ids(engine: &Engine) -> Vec<i64> {
+    match engine.run_sql("select id from orders order by id").unwrap() {
+        Output::Rows(rowset) => rowset
+            .rows
+            .iter()
+            .map(|row| match row.values().next() {
+                Some(Value::Int(id)) => *id,
+                _ => panic!("expected id"),
+            })
+            .collect(),
+        Output::Affected(_) => panic!("expected rows"),
+    }
+}
+
+fn message(engine: &Engine, sql: &str) -> String {
+    match engine.run_sql(sql) {
+        Ok(_) => panic!("expected error: {}", sql),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[test]
+fn check() {
+    let (engine, _) = setup(Vec::new());
+    engine.run_sql("create table secret(a int)").unwrap();
+    engine.run_sql("insert into secret values (1)").unwrap();
+
+    assert_eq!(
+        message(&engine, "select * from secret"),
+        "Read access to secret denied"
+    );
+    assert_eq!(
+        message(&engine, "drop table secret"),
+        "Drop access to secret denied"
+    );
+    assert_eq!(
+        message(&engine, "create table forbidden(a int)"),
+        "Create access to forbidden denied"
+    );
+    assert!(!engine.tables().unwrap().contains(&"forbidden".to_owned()));
+}
+
+// Table functions are checked by their names, like tables
+struct NoSeries;
+
+impl AccessHook for NoSeries {
+    fn check(&self, table: &str, access: Access) -> Result<()> {
+        match (table, access) {
+            ("generate_series", Access::Read) => Err("Table functions are disabled".into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn check_table_function() {
+    let (engine, _) = setup(vec![Arc::new(NoSeries)]);
+    assert_eq!(
+        message(&engine, "select * from generate_series(1, 3)"),
+        "Table functions are disabled"
+    );
+}
+
+#[test]
+fn read_predicate() {
+    let (engine, tenant) = setup(Vec::new());
+    engine
+        .run_sql("insert into orders values (1, 1, 5), (2, 1, 20)")
+        .unwrap();
+    tenant.store(2, Ordering::Relaxed);
+    engine
+        .run_sql("insert into orders values (3, 2, 30)")
+        .unwrap();
+
+    assert_eq!(ids(&engine), [3]);
+    tenant.store(1, Ordering::Relaxed);
+    assert_eq!(ids(&engine), [1, 2]);
+}
+
+#[test]
+fn insert_violates_predicate() {
+    let (engine, _) = setup(Vec::new());
+    engine
+        .run_sql("insert into orders values (1, 1, 5)")
+        .unwrap();
+
+    // the whole statement fails, including rows which match
+    assert_eq!(
+        message(&engine, "insert into orders values (2, 1, 5), (3, 2, 5)"),
+        "Row violates access policy of table orders"
+    );
+    assert_eq!(ids(&engine), [1]);
+}
+
+#[test]
+fn hooks_are_combined() {
+    let (engine, _) = setup(vec![Arc::new(ReadPredicate("amount > 10"))]);
+    engine
+        .run_sql("insert into orders values (1, 1, 5), (2, 1, 20)")
+        .unwrap();
+
+    assert_eq!(ids(&engine), [2]);
+}
+
+#[test]
+fn invalid_predicate() {
+    let (engine, _) = setup(vec![Arc::new(ReadPredicate("amount > 10 garbage"))]);
+    engine
+        .run_sql("insert into orders values (1, 1, 20)")
+        .unwrap();
+
+    assert_eq!(
+        message(&engine, "select * from orders"),
+        "Invalid access predicate of table orders: amount > 10 garbage"
+    );
+}
+
+#[test]
+fn status_hides_tables() {
+    let (engine, _) = setup(Vec::new());
+    engine.run_sql("create table secret(a int)").unwrap();
+    engine.run_sql("create table public(a int)").unwrap();
+
+    let status = match engine.run_sql("show status").unwrap() {
+        Output::Rows(rowset) => rowset
+            .rows
+            .iter()
+            .map(|row| row.values().cloned().collect::<Vec<_>>())
+            .collect::<Vec<_>>(),
+        Output::Affected(_) => panic!("expected rows"),
+    };
+    assert_eq!(
+        status[0],
+        [Value::String("tables".to_owned()), Value::Int(1)]
+    );
+    for row in &status[2..] {
+        match &row[0] {
+            Value::String(name) => assert!(name.starts_with("public."), "{}", name),
+            value => panic!("expected name, got {}", value),
+        }
+    }
+}
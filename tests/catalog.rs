@@ -0,0 +1,83 @@
+// Reopening databases, including ones created by older versions of camellia
+use camellia::{Engine, Output, Value};
+
+fn query(engine: &Engine, sql: &str) -> Vec<Vec<Value>> {
+    match engine.run_sql(sql).unwrap() {
+        Output::Rows(rowset) => rowset
+            .rows
+            .iter()
+            .map(|row| row.values().cloned().collect())
+            .collect(),
+        Output::Affected(_) => panic!("expected rows: {}", sql),
+    }
+}
+
+#[test]
+fn catalog_without_generations() {
+    let path = std::env::temp_dir().join(format!("camellia-{}-legacy.db", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+
+    {
+        let mut opts = rocksdb::Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = rocksdb::DB::open_cf(&opts, &path, ["t"]).unwrap();
+
+        // bare Schema without STRICT flag: primary_key, columns (name, Type variant)
+        let schema = (Some(0usize), vec![("id", 2u32), ("name", 3u32)]);
+        db.put(b"t", bincode::serialize(&schema).unwrap()).unwrap();
+        let row = vec![Value::Int(1), Value::String("one".to_owned())];
+        let t = db.cf_handle("t").unwrap();
+        db.put_cf(t, 1i64.to_be_bytes(), bincode::serialize(&row).unwrap())
+            .unwrap();
+    }
+
+    let engine = Engine::new(&path).unwrap();
+    let schema = engine.schema("t").unwrap();
+    assert_eq!(
+        schema.to_create_table("t"),
+        "CREATE TABLE t(id int PRIMARY KEY, name text)"
+    );
+    engine.run_sql("insert into t values (2, 'two')").unwrap();
+    engine.run_sql("create table u(a int)").unwrap();
+    engine.run_sql("insert into u values (3)").unwrap();
+    drop(engine);
+
+    // legacy and new entries side by side
+    let engine = Engine::new(&path).unwrap();
+    assert_eq!(
+        query(&engine, "select * from t"),
+        vec![
+            vec![Value::Int(1), Value::String("one".to_owned())],
+            vec![Value::Int(2), Value::String("two".to_owned())],
+        ]
+    );
+    assert_eq!(query(&engine, "select * from u"), vec![vec![Value::Int(3)]]);
+    drop(engine);
+    std::fs::remove_dir_all(&path).unwrap();
+}
+
+#[test]
+fn hidden_primary_key_after_reopen() {
+    let path = std::env::temp_dir().join(format!("camellia-{}-hidden.db", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+
+    let engine = Engine::new(&path).unwrap();
+    engine.run_sql("create table t(a int)").unwrap();
+    engine.run_sql("insert into t values (1), (2)").unwrap();
+    drop(engine);
+
+    // keys continue after the last stored one instead of overwriting it
+    let engine = Engine::new(&path).unwrap();
+    engine.run_sql("insert into t values (3)").unwrap();
+    assert_eq!(
+        query(&engine, "select a from t"),
+        vec![
+            vec![Value::Int(1)],
+            vec![Value::Int(2)],
+            vec![Value::Int(3)]
+        ]
+    );
+    drop(engine);
+    std::fs::remove_dir_all(&path).unwrap();
+}
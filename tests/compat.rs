@@ -0,0 +1,117 @@
+// rusqlite-shaped API of camellia::compat
+use camellia::compat::Connection;
+use camellia::{params, Value};
+
+fn connection() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "create table t(id int primary key, name text, admin bool);
+         insert into t values (1, 'alice', true), (2, null, false);",
+    )
+    .unwrap();
+    conn
+}
+
+fn message<T>(result: Result<T, Box<dyn std::error::Error + Send + Sync>>) -> String {
+    match result {
+        Ok(_) => panic!("expected error"),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[test]
+fn execute() {
+    let conn = connection();
+    assert_eq!(
+        conn.execute("insert into t values (?, ?, ?)", params![3, "carol", false])
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        conn.execute(
+            "insert into t values ($2, $1, $3)",
+            params!["dave", 4, true]
+        )
+        .unwrap(),
+        1
+    );
+
+    let name: String = conn
+        .query_row("select name from t where id = ?", params![4], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(name, "dave");
+
+    assert_eq!(
+        message(conn.execute("select * from t", params![])),
+        "Execute returned results, use query instead"
+    );
+    assert_eq!(
+        message(conn.execute("select * from t where id = ?", params![])),
+        "No value for parameter ?"
+    );
+    assert_eq!(
+        message(conn.prepare("select 1; select 2")),
+        "Cannot prepare more than one statement at time"
+    );
+}
+
+#[test]
+fn query() {
+    let conn = connection();
+    let mut statement = conn
+        .prepare("select id, name, admin from t where id >= ?")
+        .unwrap();
+    let rows: Vec<(i64, Option<String>, bool)> = statement
+        .query_map(params![1], |row| {
+            Ok((row.get("id")?, row.get("name")?, row.get(2)?))
+        })
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        rows,
+        [(1, Some("alice".to_owned()), true), (2, None, false)]
+    );
+
+    // statement may be run again with other parameters
+    let mut rows = statement.query(params![2]).unwrap();
+    assert_eq!(rows.column_names(), ["id", "name", "admin"]);
+    assert_eq!(
+        rows.next().unwrap().get::<_, Value>("name").unwrap(),
+        Value::Null
+    );
+    assert!(rows.next().is_none());
+}
+
+#[test]
+fn query_row_without_rows() {
+    let conn = connection();
+    let result = conn.query_row("select id from t where id = ?", params![42], |row| {
+        row.get::<_, i64>(0)
+    });
+    assert_eq!(message(result), "Query returned no rows");
+}
+
+#[test]
+fn conversion_errors() {
+    let conn = connection();
+    let get = |column: &str| {
+        conn.query_row("select * from t where id = 2", params![], |row| {
+            row.get::<_, String>(column)
+        })
+    };
+    assert_eq!(message(get("name")), "Cannot convert null to text");
+    assert_eq!(message(get("admin")), "Cannot convert bool to text");
+    assert_eq!(message(get("missing")), "Invalid column name: missing");
+
+    let result = conn.query_row("select name from t where id = 1", params![], |row| {
+        row.get::<_, i64>(0)
+    });
+    assert_eq!(message(result), "Cannot convert text to int");
+    let result = conn.query_row("select name from t where id = 1", params![], |row| {
+        row.get::<_, i64>(1)
+    });
+    assert_eq!(message(result), "Invalid column index: 1");
+}
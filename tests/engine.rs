@@ -0,0 +1,404 @@
+// Engine behaviour which sqllogictest can't check, because sqlite differs or lacks the feature
+use std::error::Error;
+use std::sync::Arc;
+
+use camellia::{Batches, Column, Engine, Output, ReadCsv, Row, Schema, TableFunction, Type, Value};
+
+fn query(engine: &Engine, sql: &str) -> Vec<Vec<Value>> {
+    match engine.run_sql(sql).unwrap() {
+        Output::Rows(rowset) => rowset
+            .rows
+            .iter()
+            .map(|row| row.values().cloned().collect())
+            .collect(),
+        Output::Affected(_) => panic!("expected rows: {}", sql),
+    }
+}
+
+fn text(s: &str) -> Value {
+    Value::String(s.to_owned())
+}
+
+#[test]
+fn audit_log() {
+    let engine = Engine::builder().audit_log(true).simulated(42);
+    engine.run_sql("create table t(a int primary key)").unwrap();
+    engine.run_sql("insert into t values (1), (2)").unwrap();
+    assert!(engine.run_sql("insert into t values (1)").is_err());
+
+    let log = query(
+        &engine,
+        "select sql, timestamp, duration_us, rows, error from camellia_audit_log",
+    );
+    assert_eq!(log.len(), 3);
+    // clock of simulated engine is frozen at unix epoch
+    assert!(log
+        .iter()
+        .all(|entry| entry[1..3] == [Value::Int(0), Value::Int(0)]));
+    assert_eq!(log[1][3..], [Value::Int(2), Value::Null]);
+    assert_eq!(
+        log[2][3..],
+        [
+            Value::Int(0),
+            text("Entry with such primary key already exist")
+        ]
+    );
+    for entry in &log[1..] {
+        let Value::String(sql) = &entry[0] else {
+            panic!("expected statement text, got {:?}", entry[0]);
+        };
+        assert!(sql.starts_with("INSERT INTO t VALUES"), "{}", sql);
+    }
+
+    // the query above is recorded as well
+    let log = query(&engine, "select rows, error from camellia_audit_log");
+    assert_eq!(log.len(), 4);
+    assert_eq!(log[3], [Value::Int(3), Value::Null]);
+}
+
+#[test]
+fn show_status() {
+    let engine = Engine::in_memory();
+    engine.run_sql("create table t1(a int)").unwrap();
+    engine
+        .run_sql("create table t2(a int primary key)")
+        .unwrap();
+    engine
+        .run_sql("insert into t1 values (1), (2), (3)")
+        .unwrap();
+
+    let status = |name: &str, value: i64| vec![text(name), Value::Int(value)];
+    assert_eq!(
+        query(&engine, "show status"),
+        vec![
+            status("tables", 2),
+            // only t1 is loaded by insert
+            status("open_tables", 1),
+            // t1, t2 and the last generation
+            status("catalog.estimated_keys", 3),
+            status("t1.rows", 3),
+            status("t1.estimated_keys", 3),
+            status("t2.rows", 0),
+            status("t2.estimated_keys", 0),
+        ]
+    );
+    assert_eq!(query(&engine, "show status")[1], status("open_tables", 2));
+}
+
+fn message(engine: &Engine, sql: &str) -> String {
+    match engine.run_sql(sql) {
+        Ok(_) => panic!("expected error: {}", sql),
+        Err(e) => e.to_string(),
+    }
+}
+
+fn error(engine: &Engine, sql: &str) -> camellia::Error {
+    let e = match engine.run_sql(sql) {
+        Ok(_) => panic!("expected error: {}", sql),
+        Err(e) => e,
+    };
+    match e.downcast_ref::<camellia::Error>() {
+        Some(e) => e.clone(),
+        None => panic!("expected typed error: {}: {}", sql, e),
+    }
+}
+
+#[test]
+fn arithmetic_errors() {
+    let engine = Engine::in_memory();
+    assert_eq!(
+        error(&engine, "select 1 / 0"),
+        camellia::Error::DivisionByZero
+    );
+    assert_eq!(
+        error(&engine, "select 1 % 0"),
+        camellia::Error::DivisionByZero
+    );
+    assert_eq!(
+        error(&engine, "select 9223372036854775807 + 1"),
+        camellia::Error::Overflow("ADD")
+    );
+    assert_eq!(
+        error(&engine, "select -9223372036854775808 - 1"),
+        camellia::Error::Overflow("SUB")
+    );
+    assert_eq!(
+        error(&engine, "select 4611686018427387904 * 2"),
+        camellia::Error::Overflow("MUL")
+    );
+    // the only overflowing division
+    assert_eq!(
+        error(&engine, "select -9223372036854775808 / -1"),
+        camellia::Error::Overflow("DIV")
+    );
+    assert_eq!(
+        query(&engine, "select -9223372036854775808 % -1"),
+        vec![vec![Value::Int(0)]]
+    );
+}
+
+#[test]
+fn null_primary_key() {
+    let engine = Engine::in_memory();
+    engine
+        .run_sql("create table t(a int primary key, b int)")
+        .unwrap();
+    let e = engine
+        .run_sql("insert into t values (null, 1)")
+        .unwrap_err();
+    assert_eq!(e.to_string(), "NULL value in primary key column a");
+    assert_eq!(query(&engine, "select * from t"), Vec::<Vec<Value>>::new());
+}
+
+#[test]
+fn wildcard_exclude_and_replace() {
+    let engine = Engine::in_memory();
+    engine
+        .run_sql("create table t(a int, b int, c text)")
+        .unwrap();
+    engine.run_sql("insert into t values (1, 2, 'x')").unwrap();
+
+    assert_eq!(
+        query(&engine, "select * exclude (b) from t"),
+        vec![vec![Value::Int(1), text("x")]]
+    );
+    assert_eq!(
+        query(&engine, "select * replace (a + 1 as a) from t"),
+        vec![vec![Value::Int(2), Value::Int(2), text("x")]]
+    );
+    assert_eq!(
+        query(
+            &engine,
+            "select t.* exclude (a, c) replace (b * 10 as b) from t"
+        ),
+        vec![vec![Value::Int(20)]]
+    );
+    assert_eq!(
+        message(&engine, "select * exclude (d) from t"),
+        "No such column: d"
+    );
+    assert_eq!(
+        message(&engine, "select * replace (1 as d) from t"),
+        "No such column: d"
+    );
+}
+
+#[test]
+fn simulated_random() {
+    let sequence = |seed| {
+        let engine = Engine::builder().simulated(seed);
+        let mut values = query(&engine, "select random(), random()").remove(0);
+        values.extend(query(&engine, "select random()").remove(0));
+        values
+    };
+
+    let first = sequence(42);
+    assert_eq!(first, sequence(42));
+    assert_ne!(first, sequence(43));
+    // values within the sequence differ
+    assert_ne!(first[0], first[1]);
+    assert_ne!(first[1], first[2]);
+}
+
+#[test]
+fn limit_by_and_fetch() {
+    let engine = Engine::in_memory();
+    engine.run_sql("create table t(a int, b int)").unwrap();
+    engine
+        .run_sql("insert into t values (1, 1), (2, 1), (3, 2), (4, 2), (5, 2), (6, 3)")
+        .unwrap();
+    let ints = |values: &[i64]| -> Vec<Vec<Value>> {
+        values.iter().map(|a| vec![Value::Int(*a)]).collect()
+    };
+
+    assert_eq!(
+        query(&engine, "select a from t limit 2 by b"),
+        ints(&[1, 2, 3, 4, 6])
+    );
+    // offset applies to each group, not to the whole result
+    assert_eq!(
+        query(&engine, "select a from t limit 1 offset 1 by b"),
+        ints(&[2, 4])
+    );
+    assert_eq!(
+        query(&engine, "select a from t limit 5 offset 2 by b"),
+        ints(&[5])
+    );
+    // terms are resolved before projection, like ORDER BY
+    assert_eq!(
+        query(&engine, "select a from t order by a desc limit 1 by t.b"),
+        ints(&[6, 5, 2])
+    );
+    assert_eq!(
+        query(
+            &engine,
+            "select a, b % 2 as parity from t limit 1 by parity"
+        ),
+        vec![
+            vec![Value::Int(1), Value::Int(1)],
+            vec![Value::Int(3), Value::Int(0)],
+        ]
+    );
+    assert_eq!(
+        query(
+            &engine,
+            "select a from t order by a desc fetch first 2 rows only"
+        ),
+        ints(&[6, 5])
+    );
+    assert_eq!(
+        query(&engine, "select a from t fetch first row only"),
+        ints(&[1])
+    );
+}
+
+#[test]
+fn numeric_literals() {
+    let engine = Engine::in_memory();
+    assert_eq!(
+        error(&engine, "select 9223372036854775808"),
+        camellia::Error::OutOfRange("9223372036854775808".to_owned())
+    );
+    assert_eq!(
+        error(&engine, "select 0x10000000000000000"),
+        camellia::Error::OutOfRange("0x10000000000000000".to_owned())
+    );
+    assert_eq!(
+        query(&engine, "select -9223372036854775808, 0xff"),
+        [[Value::Int(i64::MIN), Value::Int(255)]]
+    );
+
+    // sqlite reads X'FF' as a blob, which has no camellia type
+    match error(&engine, "select 1,\n  x'FF'") {
+        camellia::Error::Parse {
+            message,
+            line,
+            column,
+            token,
+        } => {
+            assert_eq!(message, "Blob literals are not supported");
+            assert_eq!((line, column), (2, 3));
+            assert_eq!(token.as_deref(), Some("X'FF'"));
+        }
+        e => panic!("expected parse error: {}", e),
+    }
+    assert_eq!(
+        message(&engine, "select X'0a' + 1"),
+        "Blob literals are not supported at line 1, column 8"
+    );
+    assert_eq!(query(&engine, "select 0x0a + 1"), [[Value::Int(11)]]);
+}
+
+#[test]
+fn parse_errors() {
+    let engine = Engine::in_memory();
+    let parse_error = |sql| match error(&engine, sql) {
+        camellia::Error::Parse {
+            message,
+            line,
+            column,
+            token,
+        } => (message, line, column, token),
+        e => panic!("expected parse error: {}", e),
+    };
+
+    // location reported by sqlparser as a part of message
+    assert_eq!(
+        parse_error("select 1,\n  2 3"),
+        (
+            "Expected end of statement, found: 3".to_owned(),
+            2,
+            5,
+            Some("3".to_owned())
+        )
+    );
+    // unexpected end of input has no location, points right after the last character
+    assert_eq!(
+        parse_error("select\n  1 +"),
+        ("Expected an expression:, found: EOF".to_owned(), 2, 6, None)
+    );
+}
+
+// numbers(n, width): n rows of a single INT column, each row has |width| values
+struct Numbers;
+
+impl TableFunction for Numbers {
+    fn call(&self, args: &[Value]) -> Result<(Schema, Batches), Box<dyn Error + Send + Sync>> {
+        let n = args[0].to_int().ok_or("Expected number of rows")?;
+        let width = args[1].to_int().ok_or("Expected row width")?;
+        let schema = Schema {
+            primary_key: None,
+            columns: vec![Column {
+                name: "n".to_owned(),
+                type_: Type::Integer,
+            }],
+            strict: false,
+        };
+        let rows: Vec<_> = (0..n)
+            .map(|i| Row::from(vec![Value::Int(i); width as usize]))
+            .collect();
+        // every row in a separate batch, including empty ones
+        let batches = rows
+            .into_iter()
+            .flat_map(|row| [Ok(Vec::new()), Ok(vec![row])]);
+        Ok((schema, Box::new(batches)))
+    }
+}
+
+#[test]
+fn table_function() {
+    let engine = Engine::builder()
+        .table_function("Numbers", Arc::new(Numbers))
+        .in_memory();
+
+    assert_eq!(
+        query(&engine, "select n * 10 from numbers(3, 1) where n > 0"),
+        [[Value::Int(10)], [Value::Int(20)]]
+    );
+    assert_eq!(
+        message(&engine, "select * from numbers(2, 2)"),
+        "numbers(2, 2) returned row of 2 value(s), expected 1"
+    );
+    assert_eq!(
+        message(&engine, "select * from numbers(2, 0)"),
+        "numbers(2, 0) returned row of 0 value(s), expected 1"
+    );
+}
+
+#[test]
+fn read_csv() {
+    let engine = Engine::builder()
+        .table_function("read_csv", Arc::new(ReadCsv))
+        .in_memory();
+    assert_eq!(
+        query(
+            &engine,
+            "select name, age, admin from read_csv('plans/people.csv')"
+        ),
+        [
+            [text("alice"), Value::Int(31), Value::Bool(true)],
+            [text("bob"), Value::Int(25), Value::Bool(false)],
+            // quoted comma, empty field is NULL
+            [text("smith, jr"), Value::Null, Value::Bool(false)],
+        ]
+    );
+
+    let path = std::env::temp_dir().join(format!("camellia-read-csv-{}.csv", std::process::id()));
+    let path = path.to_str().unwrap();
+    // header names are case-insensitive, same as unquoted identifiers
+    std::fs::write(path, "Name,AGE,\nalice,31,x\n").unwrap();
+    assert_eq!(
+        query(
+            &engine,
+            &format!("select name, age, column3 from read_csv('{}')", path)
+        ),
+        [[text("alice"), Value::Int(31), text("x")]]
+    );
+
+    std::fs::write(path, "a,A\n1,2\n").unwrap();
+    assert_eq!(
+        message(&engine, &format!("select * from read_csv('{}')", path)),
+        format!("read_csv: {}: Duplicate column name: a", path)
+    );
+    std::fs::remove_file(path).unwrap();
+}
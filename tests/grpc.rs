@@ -0,0 +1,99 @@
+// Execute of camellia::grpc::Service, called directly without transport
+use std::sync::Arc;
+
+use futures::StreamExt;
+use tonic::Request;
+
+use camellia::grpc::proto::camellia_server::Camellia;
+use camellia::grpc::proto::execute_response::Payload;
+use camellia::grpc::proto::{self, value::Kind};
+use camellia::grpc::Service;
+use camellia::Engine;
+
+async fn execute(
+    service: &Service,
+    sql: &str,
+    params: Vec<Kind>,
+) -> Result<Vec<Payload>, tonic::Status> {
+    let request = proto::ExecuteRequest {
+        sql: sql.to_owned(),
+        params: params
+            .into_iter()
+            .map(|kind| proto::Value { kind: Some(kind) })
+            .collect(),
+    };
+    let mut stream = service.execute(Request::new(request)).await?.into_inner();
+    let mut payloads = Vec::new();
+    while let Some(response) = stream.next().await {
+        payloads.push(response?.payload.unwrap());
+    }
+    Ok(payloads)
+}
+
+fn int(val: i64) -> proto::Value {
+    proto::Value {
+        kind: Some(Kind::Int(val)),
+    }
+}
+
+#[tokio::test]
+async fn params_and_batches() {
+    let engine = Arc::new(Engine::in_memory());
+    let service = Service::new(engine);
+    let payloads = execute(
+        &service,
+        "create table t(a int primary key, b text)",
+        vec![],
+    )
+    .await
+    .unwrap();
+    assert_eq!(payloads, vec![Payload::Affected(0)]);
+
+    let payloads = execute(
+        &service,
+        "insert into t select value, $1 from generate_series(1, $2)",
+        vec![Kind::Text("x".to_owned()), Kind::Int(1500)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(payloads, vec![Payload::Affected(1500)]);
+
+    let payloads = execute(
+        &service,
+        "select a from t where a > ?",
+        vec![Kind::Int(100)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(payloads.len(), 3);
+    assert_eq!(
+        payloads[0],
+        Payload::Header(proto::Header {
+            columns: vec![proto::Column {
+                name: "a".to_owned(),
+                r#type: proto::Type::Integer as i32,
+            }],
+        })
+    );
+
+    // rows are split into batches of 1024
+    let batches: Vec<_> = payloads[1..]
+        .iter()
+        .map(|payload| match payload {
+            Payload::Batch(batch) => batch.rows.clone(),
+            payload => panic!("expected batch, got {:?}", payload),
+        })
+        .collect();
+    assert_eq!(batches[0].len(), 1024);
+    assert_eq!(batches[1].len(), 1400 - 1024);
+    assert_eq!(batches[0][0].values, vec![int(101)]);
+    assert_eq!(batches[1].last().unwrap().values, vec![int(1500)]);
+}
+
+#[tokio::test]
+async fn missing_param() {
+    let service = Service::new(Arc::new(Engine::in_memory()));
+    let status = execute(&service, "select ?", vec![]).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    assert_eq!(status.message(), "No value for parameter ?");
+}
@@ -0,0 +1,118 @@
+// POST /query of camellia::http, requests are sent to the router without binding a socket
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use camellia::{http, Engine, Permissions, StaticUsers};
+
+async fn post(router: Router, sql: &str) -> (StatusCode, Value) {
+    post_as(router, None, sql).await
+}
+
+// Same as post, with |authorization| header if any
+async fn post_as(router: Router, authorization: Option<&str>, sql: &str) -> (StatusCode, Value) {
+    let mut request = Request::post("/query");
+    if let Some(authorization) = authorization {
+        request = request.header(header::AUTHORIZATION, authorization);
+    }
+    let request = request.body(Body::from(sql.to_owned())).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn engine() -> Arc<Engine> {
+    let engine = Engine::in_memory();
+    engine
+        .run_sql("create table t(a int primary key, b text)")
+        .unwrap();
+    Arc::new(engine)
+}
+
+#[tokio::test]
+async fn rows() {
+    let engine = engine();
+    engine
+        .run_sql("insert into t values (1, 'one'), (2, null)")
+        .unwrap();
+
+    let (status, body) = post(http::router(engine), "select a, b from t").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        json!({
+            "columns": [{"name": "a", "type": "int"}, {"name": "b", "type": "text"}],
+            "rows": [[1, "one"], [2, null]],
+        })
+    );
+}
+
+#[tokio::test]
+async fn rows_span_several_chunks() {
+    let engine = engine();
+    engine
+        .run_sql("insert into t select value, 'x' from generate_series(1, 3000)")
+        .unwrap();
+
+    let (status, body) = post(http::router(engine), "select a from t").await;
+    assert_eq!(status, StatusCode::OK);
+    let rows = body["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 3000);
+    assert_eq!(rows[2999], json!([3000]));
+}
+
+#[tokio::test]
+async fn affected() {
+    let (status, body) = post(http::router(engine()), "insert into t values (1, 'one')").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "affected": 1 }));
+}
+
+#[tokio::test]
+async fn error() {
+    let (status, body) = post(http::router(engine()), "select * from missing").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, json!({ "error": "No such table" }));
+
+    // every request gets a new session
+    let (status, body) = post(http::router(engine()), "SET dialect = 'mysql'").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        json!({ "error": "SET dialect is not supported, session ends with the request" })
+    );
+}
+
+#[tokio::test]
+async fn unauthorized() {
+    let users = Arc::new(StaticUsers::new().user("admin", "secret", Permissions::ALL));
+    let router = http::router_with_auth(engine(), users);
+    let missing = json!({ "error": "Missing credentials" });
+    let invalid = json!({ "error": "Invalid user name or password" });
+
+    let cases = [
+        (None, &missing),
+        // admin:wrong
+        (Some("Basic YWRtaW46d3Jvbmc="), &invalid),
+        (Some("Basic not base64!"), &missing),
+        // admin, no colon
+        (Some("Basic YWRtaW4="), &missing),
+        (Some("Bearer YWRtaW46c2VjcmV0"), &missing),
+    ];
+    for (authorization, error) in cases {
+        let (status, body) = post_as(router.clone(), authorization, "select 1").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{:?}", authorization);
+        assert_eq!(body, *error, "{:?}", authorization);
+    }
+
+    // admin:secret
+    let (status, _) = post_as(router, Some("Basic YWRtaW46c2VjcmV0"), "select 1").await;
+    assert_eq!(status, StatusCode::OK);
+}
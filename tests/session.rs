@@ -0,0 +1,101 @@
+// Per-client state and permissions of Session, see also tests/http.rs and tests/grpc.rs
+use std::sync::Arc;
+
+use camellia::session::Session;
+use camellia::{Dialect, Engine, Permissions, StaticUsers};
+
+fn users() -> StaticUsers {
+    StaticUsers::new()
+        .user("reader", "secret", Permissions::READ_ONLY)
+        .user("writer", "secret", Permissions::READ_WRITE)
+}
+
+// Session of |user| of users(), with table t created
+fn login(user: &str) -> Session {
+    let engine = Engine::in_memory();
+    engine.run_sql("create table t(a int)").unwrap();
+    match Session::authenticate(Arc::new(engine), &users(), user, "secret") {
+        Ok(session) => session,
+        Err(e) => panic!("{}: {}", user, e),
+    }
+}
+
+fn message(session: &mut Session, sql: &str) -> String {
+    match session.execute(sql, &[]) {
+        Ok(_) => panic!("expected error: {}", sql),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[test]
+fn dialect() {
+    let engine = Arc::new(Engine::builder().dialect(Dialect::Sqlite).in_memory());
+    let mut first = Session::new(engine.clone());
+    let second = Session::new(engine.clone());
+    assert_eq!(first.dialect(), Dialect::Sqlite);
+
+    first.execute("SET dialect = 'mysql'", &[]).unwrap();
+    assert_eq!(first.dialect(), Dialect::MySql);
+    // other sessions and the engine keep their dialect
+    assert_eq!(second.dialect(), Dialect::Sqlite);
+    assert_eq!(engine.dialect(), Dialect::Sqlite);
+    assert_eq!(Session::new(engine.clone()).dialect(), Dialect::Sqlite);
+
+    first.execute("SET dialect = postgres", &[]).unwrap();
+    assert_eq!(first.dialect(), Dialect::Postgres);
+    assert!(first.execute("SET dialect = 'cobol'", &[]).is_err());
+    assert_eq!(first.dialect(), Dialect::Postgres);
+
+    assert!(engine.run_sql("SET dialect = 'mysql'").is_err());
+
+    // would be lost with the session, e.g. of http request
+    let mut single = Session::new(engine.clone()).single_request();
+    assert_eq!(
+        message(&mut single, "SET dialect = 'mysql'"),
+        "SET dialect is not supported, session ends with the request"
+    );
+    assert_eq!(single.dialect(), Dialect::Sqlite);
+}
+
+#[test]
+fn wrong_password() {
+    let engine = Arc::new(Engine::in_memory());
+    for (user, password) in [("reader", "wrong"), ("reader", ""), ("nobody", "secret")] {
+        match Session::authenticate(engine.clone(), &users(), user, password) {
+            Ok(_) => panic!("{}:{} authenticated", user, password),
+            Err(e) => assert_eq!(e.to_string(), "Invalid user name or password"),
+        }
+    }
+}
+
+#[test]
+fn read_only() {
+    let mut session = login("reader");
+    assert_eq!(session.user(), Some("reader"));
+    session.execute("select * from t", &[]).unwrap();
+
+    assert_eq!(
+        message(&mut session, "insert into t values (1)"),
+        "Permission denied: write statements are not allowed for user reader"
+    );
+    assert_eq!(
+        message(&mut session, "drop table t"),
+        "Permission denied: DDL statements are not allowed for user reader"
+    );
+    // dialect is the state of the session, not of the database
+    session.execute("SET dialect = 'mysql'", &[]).unwrap();
+    assert_eq!(session.dialect(), Dialect::MySql);
+    assert!(session.engine().tables().unwrap().contains(&"t".to_owned()));
+}
+
+#[test]
+fn read_write() {
+    let mut session = login("writer");
+    session.execute("insert into t values (1)", &[]).unwrap();
+
+    assert_eq!(
+        message(&mut session, "create table u(a int)"),
+        "Permission denied: DDL statements are not allowed for user writer"
+    );
+    assert!(!session.engine().tables().unwrap().contains(&"u".to_owned()));
+}